@@ -0,0 +1,23 @@
+package com.testprism;
+
+import org.junit.jupiter.api.BeforeAll;
+import org.junit.jupiter.api.Test;
+
+import static org.junit.jupiter.api.Assertions.assertTrue;
+
+public class BeforeAllFailureTest {
+    @BeforeAll
+    static void setUp() {
+        throw new IllegalStateException("Setup failed");
+    }
+
+    @Test
+    void firstTest() {
+        assertTrue(true);
+    }
+
+    @Test
+    void secondTest() {
+        assertTrue(true);
+    }
+}
@@ -0,0 +1,374 @@
+use crate::test_models::TestAttachment;
+use crate::test_report::Attachment;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default)]
+pub struct AttachmentOptions {
+    /// Directory the referenced files are copied into, one subdirectory per test.
+    pub bundle_dir: Option<PathBuf>,
+    /// Directory of the generated report, the paths of copied files are relative to it.
+    pub report_dir: PathBuf,
+    /// Files up to this size in bytes are inlined as base64, `0` disables inlining.
+    pub inline_max_bytes: u64,
+}
+
+/// Converts the attachments referenced by a report into report attachments. Relative paths
+/// are resolved against the directory of the report that references them.
+pub fn resolve_attachments(
+    attachments: &[TestAttachment],
+    source_dir: Option<&Path>,
+    test_id: &str,
+    options: &AttachmentOptions,
+) -> Vec<Attachment> {
+    attachments
+        .iter()
+        .map(|attachment| {
+            let source_path = source_path(&attachment.path, source_dir);
+            let mut result = Attachment {
+                name: attachment.name.clone().unwrap_or_else(|| {
+                    file_name(&source_path).unwrap_or_else(|| attachment.path.clone())
+                }),
+                mime_type: attachment
+                    .mime_type
+                    .clone()
+                    .unwrap_or_else(|| guess_mime_type(&attachment.path).to_string()),
+                path: Some(attachment.path.clone()),
+                content: None,
+            };
+
+            if let Err(e) = store_attachment(&mut result, &source_path, test_id, options) {
+                eprintln!("Failed to read attachment {}: {}", attachment.path, e);
+            }
+            result
+        })
+        .collect()
+}
+
+fn store_attachment(
+    attachment: &mut Attachment,
+    source_path: &Path,
+    test_id: &str,
+    options: &AttachmentOptions,
+) -> Result<(), String> {
+    if options.inline_max_bytes == 0 && options.bundle_dir.is_none() {
+        return Ok(());
+    }
+
+    let size = fs::metadata(source_path)
+        .map_err(|e| format!("I/O error: {}", e))?
+        .len();
+    if options.inline_max_bytes > 0 && size <= options.inline_max_bytes {
+        let content = fs::read(source_path).map_err(|e| format!("I/O error: {}", e))?;
+        attachment.content = Some(STANDARD.encode(content));
+        attachment.path = None;
+        return Ok(());
+    }
+
+    if let Some(bundle_dir) = &options.bundle_dir {
+        let target_dir = bundle_dir.join(test_id);
+        fs::create_dir_all(&target_dir).map_err(|e| format!("I/O error: {}", e))?;
+        let target_path = bundle_path(&target_dir, &attachment.name, source_path)?;
+        if !target_path.exists() {
+            fs::copy(source_path, &target_path).map_err(|e| format!("I/O error: {}", e))?;
+        }
+        attachment.path = Some(relative_path(&target_path, &options.report_dir));
+    }
+
+    Ok(())
+}
+
+fn source_path(path: &str, source_dir: Option<&Path>) -> PathBuf {
+    let path = Path::new(path);
+    match source_dir {
+        Some(source_dir) if path.is_relative() && source_dir.join(path).exists() => {
+            source_dir.join(path)
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+}
+
+/// Free path for the file in `directory`, or the path of a file with the same content, so
+/// parsing the same reports again does not duplicate the bundled files.
+fn bundle_path(directory: &Path, name: &str, source_path: &Path) -> Result<PathBuf, String> {
+    let file_name = Path::new(name)
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "attachment".to_string());
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (file_name.as_str(), String::new()),
+    };
+
+    for index in 0.. {
+        let candidate = match index {
+            0 => directory.join(&file_name),
+            _ => directory.join(format!("{}-{}{}", stem, index, extension)),
+        };
+        if !candidate.exists() || same_content(&candidate, source_path)? {
+            return Ok(candidate);
+        }
+    }
+    unreachable!("Unbounded range always yields a path")
+}
+
+fn same_content(a: &Path, b: &Path) -> Result<bool, String> {
+    let read = |path: &Path| fs::read(path).map_err(|e| format!("I/O error: {}", e));
+    let same_size = fs::metadata(a)
+        .map_err(|e| format!("I/O error: {}", e))?
+        .len()
+        == fs::metadata(b)
+            .map_err(|e| format!("I/O error: {}", e))?
+            .len();
+    Ok(same_size && read(a)? == read(b)?)
+}
+
+/// Path of `path` relative to `base` with `/` separators, using `..` to leave `base`, or
+/// the absolute path if they share no root.
+fn relative_path(path: &Path, base: &Path) -> String {
+    let (Ok(path), Ok(base)) = (path.canonicalize(), base.canonicalize()) else {
+        return path.to_string_lossy().to_string();
+    };
+    let common = path
+        .components()
+        .zip(base.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 0 {
+        return path.to_string_lossy().to_string();
+    }
+
+    let parents = base.components().skip(common).map(|_| "..".to_string());
+    let children = path
+        .components()
+        .skip(common)
+        .map(|component| component.as_os_str().to_string_lossy().to_string());
+    parents.chain(children).collect::<Vec<_>>().join("/")
+}
+
+pub fn guess_mime_type(path: &str) -> &'static str {
+    let extension = path
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "webm" => "video/webm",
+        "mp4" => "video/mp4",
+        "zip" => "application/zip",
+        "har" | "json" => "application/json",
+        "html" | "htm" => "text/html",
+        "xml" => "application/xml",
+        "txt" | "log" => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parameterized::parameterized;
+    use tempfile::tempdir;
+
+    fn attachment(path: &str) -> TestAttachment {
+        TestAttachment {
+            name: None,
+            path: path.to_string(),
+            mime_type: None,
+        }
+    }
+
+    #[parameterized(path = {
+        "screenshots/login.PNG",
+        "trace.zip",
+        "network.har",
+        "video.webm",
+        "README",
+    }, expected = {
+        "image/png",
+        "application/zip",
+        "application/json",
+        "video/webm",
+        "application/octet-stream",
+    })]
+    fn guess_mime_type_from_extension(path: &str, expected: &str) {
+        assert_eq!(guess_mime_type(path), expected);
+    }
+
+    #[test]
+    fn keeps_path_as_referenced_without_bundle_or_inlining() {
+        let result = resolve_attachments(
+            &[attachment("screenshots/login.png")],
+            None,
+            "id",
+            &AttachmentOptions::default(),
+        );
+
+        assert_eq!(
+            result,
+            vec![Attachment {
+                name: "login.png".to_string(),
+                mime_type: "image/png".to_string(),
+                path: Some("screenshots/login.png".to_string()),
+                content: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn inlines_small_files_relative_to_the_report() {
+        let source_dir = tempdir().unwrap();
+        fs::write(source_dir.path().join("log.txt"), "hello").unwrap();
+
+        let result = resolve_attachments(
+            &[attachment("log.txt")],
+            Some(source_dir.path()),
+            "id",
+            &AttachmentOptions {
+                inline_max_bytes: 1024,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(result[0].content, Some("aGVsbG8=".to_string()));
+        assert_eq!(result[0].path, None);
+    }
+
+    #[test]
+    fn copies_large_files_into_the_bundle() {
+        let source_dir = tempdir().unwrap();
+        let report_dir = tempdir().unwrap();
+        fs::write(source_dir.path().join("shot.png"), [0u8; 64]).unwrap();
+        let options = AttachmentOptions {
+            bundle_dir: Some(report_dir.path().join("attachments")),
+            report_dir: report_dir.path().to_path_buf(),
+            inline_max_bytes: 16,
+        };
+
+        let result = resolve_attachments(
+            &[attachment("shot.png"), attachment("shot.png")],
+            Some(source_dir.path()),
+            "abc",
+            &options,
+        );
+
+        assert_eq!(result[0].path, Some("attachments/abc/shot.png".to_string()));
+        assert_eq!(result[1].path, result[0].path);
+        assert!(report_dir.path().join("attachments/abc/shot.png").exists());
+        assert_eq!(result[0].content, None);
+    }
+
+    #[test]
+    fn renames_different_files_with_the_same_name() {
+        let source_dir = tempdir().unwrap();
+        let report_dir = tempdir().unwrap();
+        fs::create_dir_all(source_dir.path().join("retry")).unwrap();
+        fs::write(source_dir.path().join("shot.png"), [0u8; 64]).unwrap();
+        fs::write(source_dir.path().join("retry/shot.png"), [1u8; 64]).unwrap();
+        let options = AttachmentOptions {
+            bundle_dir: Some(report_dir.path().join("attachments")),
+            report_dir: report_dir.path().to_path_buf(),
+            inline_max_bytes: 0,
+        };
+
+        for _ in 0..2 {
+            let result = resolve_attachments(
+                &[attachment("shot.png"), attachment("retry/shot.png")],
+                Some(source_dir.path()),
+                "abc",
+                &options,
+            );
+
+            assert_eq!(result[0].path, Some("attachments/abc/shot.png".to_string()));
+            assert_eq!(
+                result[1].path,
+                Some("attachments/abc/shot-1.png".to_string())
+            );
+        }
+        assert_eq!(
+            fs::read_dir(report_dir.path().join("attachments/abc"))
+                .unwrap()
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn bundles_empty_files_when_inlining_is_disabled() {
+        let source_dir = tempdir().unwrap();
+        let report_dir = tempdir().unwrap();
+        fs::write(source_dir.path().join("empty.log"), []).unwrap();
+        let options = AttachmentOptions {
+            bundle_dir: Some(report_dir.path().join("attachments")),
+            report_dir: report_dir.path().to_path_buf(),
+            inline_max_bytes: 0,
+        };
+
+        let result = resolve_attachments(
+            &[attachment("empty.log")],
+            Some(source_dir.path()),
+            "abc",
+            &options,
+        );
+
+        assert_eq!(
+            result[0].path,
+            Some("attachments/abc/empty.log".to_string())
+        );
+        assert_eq!(result[0].content, None);
+        assert!(report_dir.path().join("attachments/abc/empty.log").exists());
+    }
+
+    #[test]
+    fn bundle_outside_the_report_directory_gets_a_relative_path() {
+        let root = tempdir().unwrap();
+        let source_dir = root.path().join("results");
+        let report_dir = root.path().join("site/report");
+        fs::create_dir_all(&source_dir).unwrap();
+        fs::create_dir_all(&report_dir).unwrap();
+        fs::write(source_dir.join("shot.png"), [0u8; 64]).unwrap();
+        let options = AttachmentOptions {
+            bundle_dir: Some(root.path().join("attachments")),
+            report_dir,
+            inline_max_bytes: 0,
+        };
+
+        let result = resolve_attachments(
+            &[attachment("shot.png")],
+            Some(&source_dir),
+            "abc",
+            &options,
+        );
+
+        assert_eq!(
+            result[0].path,
+            Some("../../attachments/abc/shot.png".to_string())
+        );
+    }
+
+    #[test]
+    fn keeps_reference_to_missing_file() {
+        let report_dir = tempdir().unwrap();
+        let options = AttachmentOptions {
+            bundle_dir: Some(report_dir.path().join("attachments")),
+            report_dir: report_dir.path().to_path_buf(),
+            inline_max_bytes: 0,
+        };
+
+        let result = resolve_attachments(&[attachment("missing.png")], None, "abc", &options);
+
+        assert_eq!(result[0].path, Some("missing.png".to_string()));
+    }
+}
@@ -0,0 +1,330 @@
+use crate::failure_signature::FailureSignature;
+use crate::output_format::{OutputFormat, escape_markdown_cell, test_label};
+use crate::report_io::{read_report, write_output, write_report};
+use crate::test_report::{TestExecution, TestReport};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct ClustersOptions {
+    pub input: String,
+    pub output: Option<String>,
+    pub format: OutputFormat,
+    /// Number of example tests to list per cluster.
+    pub examples: usize,
+    /// Prefixes of the stack frames of the project, e.g. `com.example.`.
+    pub project_prefixes: Vec<String>,
+    /// Whether to set the cluster id of every failed execution in the report.
+    pub stamp: bool,
+    /// Path of the stamped report, defaults to overwriting the input.
+    pub stamped_output: Option<String>,
+}
+
+impl Default for ClustersOptions {
+    fn default() -> Self {
+        ClustersOptions {
+            input: String::new(),
+            output: None,
+            format: OutputFormat::Text,
+            examples: 5,
+            project_prefixes: Vec::new(),
+            stamp: false,
+            stamped_output: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClusterTest {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FailureCluster {
+    pub id: String,
+    /// Failure message with the values that change between failures replaced.
+    pub message: String,
+    /// Top stack frame of the project shared by the failures.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame: Option<String>,
+    pub count: usize,
+    pub examples: Vec<ClusterTest>,
+}
+
+pub fn clusters_command(options: ClustersOptions) {
+    let mut report = read_report(&options.input).expect("Failed to read input file");
+
+    let clusters = find_clusters(&report, options.examples, &options.project_prefixes);
+    let content = match options.format {
+        OutputFormat::Text => format_text(&clusters),
+        OutputFormat::Json => format!(
+            "{}\n",
+            serde_json::to_string_pretty(&clusters).expect("Failed to serialize the clusters")
+        ),
+        OutputFormat::Markdown => format_markdown(&clusters),
+    };
+    write_output(&content, options.output.as_deref()).expect("Failed to write the clusters");
+
+    if options.stamp {
+        stamp_cluster_ids(&mut report, &options.project_prefixes);
+        let stamped_output = options.stamped_output.unwrap_or(options.input);
+        write_report(&report, &stamped_output).expect("Failed to write report");
+    }
+}
+
+/// Groups the tests whose latest execution failed by the signature of the failure, largest
+/// cluster first.
+pub fn find_clusters(
+    report: &TestReport,
+    examples: usize,
+    project_prefixes: &[String],
+) -> Vec<FailureCluster> {
+    let mut clusters: Vec<FailureCluster> = Vec::new();
+    let mut cluster_indexes: HashMap<String, usize> = HashMap::new();
+
+    for test in &report.tests {
+        let Some(execution) = test
+            .latest_execution()
+            .filter(|execution| execution.status.is_failure())
+        else {
+            continue;
+        };
+
+        let signature = signature(execution, project_prefixes);
+        let id = signature.id();
+        let index = *cluster_indexes.entry(id.clone()).or_insert_with(|| {
+            clusters.push(FailureCluster {
+                id,
+                message: signature.message,
+                frame: signature.frame,
+                count: 0,
+                examples: Vec::new(),
+            });
+            clusters.len() - 1
+        });
+
+        let cluster = &mut clusters[index];
+        cluster.count += 1;
+        if cluster.examples.len() < examples {
+            cluster.examples.push(ClusterTest {
+                id: test.id.clone(),
+                name: test.name.clone(),
+                path: test.path.clone(),
+            });
+        }
+    }
+
+    clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.count));
+    clusters
+}
+
+/// Sets the cluster id of every failed or errored execution, including older ones.
+pub fn stamp_cluster_ids(report: &mut TestReport, project_prefixes: &[String]) {
+    for execution in report
+        .tests
+        .iter_mut()
+        .flat_map(|test| test.executions.iter_mut())
+        .filter(|execution| execution.status.is_failure())
+    {
+        execution.cluster_id = Some(signature(execution, project_prefixes).id());
+    }
+}
+
+fn signature(execution: &TestExecution, project_prefixes: &[String]) -> FailureSignature {
+    FailureSignature::new(
+        execution.message.as_deref(),
+        execution.stack_trace.as_deref(),
+        project_prefixes,
+    )
+}
+
+fn format_text(clusters: &[FailureCluster]) -> String {
+    if clusters.is_empty() {
+        return "No failures.\n".to_string();
+    }
+
+    let mut text = String::new();
+    for cluster in clusters {
+        text.push_str(&format!(
+            "{} failures [{}]: {}\n",
+            cluster.count, cluster.id, cluster.message
+        ));
+        if let Some(frame) = &cluster.frame {
+            text.push_str(&format!("  at {}\n", frame));
+        }
+        for example in &cluster.examples {
+            text.push_str(&format!(
+                "  - {}\n",
+                test_label(&example.path, &example.name)
+            ));
+        }
+    }
+    text
+}
+
+fn format_markdown(clusters: &[FailureCluster]) -> String {
+    let mut markdown = String::from(
+        "## Failure clusters\n\n| Failures | Message | Frame | Examples |\n| ---: | --- | --- | --- |\n",
+    );
+    for cluster in clusters {
+        let examples: Vec<String> = cluster
+            .examples
+            .iter()
+            .map(|example| test_label(&example.path, &example.name))
+            .collect();
+        markdown.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            cluster.count,
+            escape_markdown_cell(&cluster.message),
+            escape_markdown_cell(cluster.frame.as_deref().unwrap_or("-")),
+            escape_markdown_cell(&examples.join("<br>"))
+        ));
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+    use crate::test_report::{REPORT_VERSION, TestExecutionStatus, TestReportTest};
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn create_execution(
+        status: TestExecutionStatus,
+        message: &str,
+        stack_trace: Option<&str>,
+    ) -> TestExecution {
+        TestExecution {
+            message: Some(message.to_string()),
+            stack_trace: stack_trace.map(|stack_trace| stack_trace.to_string()),
+            ..test_fixtures::create_execution(status)
+        }
+    }
+
+    fn create_test(name: &str, execution: TestExecution) -> TestReportTest {
+        TestReportTest {
+            id: name.to_string(),
+            name: name.to_string(),
+            path: "suite".to_string(),
+            executions: vec![execution],
+            ..Default::default()
+        }
+    }
+
+    fn create_report() -> TestReport {
+        let db_frame = "at com.example.Db.connect(Db.java:12)";
+        TestReport {
+            version: REPORT_VERSION,
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            runs: Vec::new(),
+            suites: Vec::new(),
+            tests: vec![
+                create_test(
+                    "a",
+                    create_execution(TestExecutionStatus::Failed, "Expected 1 but was 2", None),
+                ),
+                create_test(
+                    "b",
+                    create_execution(
+                        TestExecutionStatus::Error,
+                        "Connection refused after 5000 ms",
+                        Some(db_frame),
+                    ),
+                ),
+                create_test("c", create_execution(TestExecutionStatus::Passed, "", None)),
+                create_test(
+                    "d",
+                    create_execution(
+                        TestExecutionStatus::Error,
+                        "Connection refused after 3000 ms",
+                        Some(db_frame),
+                    ),
+                ),
+                create_test(
+                    "e",
+                    create_execution(
+                        TestExecutionStatus::Error,
+                        "Connection refused after 3000 ms",
+                        Some(db_frame),
+                    ),
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn failures_are_grouped_by_signature_largest_first() {
+        let clusters = find_clusters(&create_report(), 2, &[]);
+
+        let summary: Vec<(usize, &str, Option<&str>, Vec<&str>)> = clusters
+            .iter()
+            .map(|cluster| {
+                (
+                    cluster.count,
+                    cluster.message.as_str(),
+                    cluster.frame.as_deref(),
+                    cluster
+                        .examples
+                        .iter()
+                        .map(|example| example.name.as_str())
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    3,
+                    "Connection refused after <n> ms",
+                    Some("com.example.Db.connect"),
+                    vec!["b", "d"],
+                ),
+                (1, "Expected <n> but was <n>", None, vec!["a"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn clusters_command_stamps_cluster_ids() {
+        let mut input_file = NamedTempFile::new().unwrap();
+        input_file
+            .write_all(serde_json::to_string(&create_report()).unwrap().as_bytes())
+            .unwrap();
+        let output_file = NamedTempFile::new().unwrap();
+        let stamped_file = NamedTempFile::new().unwrap();
+
+        clusters_command(ClustersOptions {
+            input: input_file.path().to_str().unwrap().to_string(),
+            output: Some(output_file.path().to_str().unwrap().to_string()),
+            format: OutputFormat::Json,
+            stamp: true,
+            stamped_output: Some(stamped_file.path().to_str().unwrap().to_string()),
+            ..Default::default()
+        });
+
+        let clusters: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(output_file.path()).unwrap()).unwrap();
+        let stamped = read_report(stamped_file.path().to_str().unwrap()).unwrap();
+        let cluster_ids: Vec<Option<&str>> = stamped
+            .tests
+            .iter()
+            .map(|test| test.executions[0].cluster_id.as_deref())
+            .collect();
+        let db_cluster = clusters[0]["id"].as_str();
+        assert_eq!(
+            cluster_ids,
+            vec![
+                clusters[1]["id"].as_str(),
+                db_cluster,
+                None,
+                db_cluster,
+                db_cluster,
+            ]
+        );
+    }
+}
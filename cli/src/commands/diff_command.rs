@@ -0,0 +1,466 @@
+use crate::output_format::{OutputFormat, escape_markdown_cell, test_label};
+use crate::report_io::{read_report, write_output};
+use crate::test_report::{TestExecutionStatus, TestReport, TestReportTest};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum DiffCategory {
+    NewlyFailing,
+    NewlyPassing,
+    NewlySkipped,
+    Slower,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    pub base: String,
+    pub head: String,
+    pub output: Option<String>,
+    pub format: OutputFormat,
+    pub fail_on: Vec<DiffCategory>,
+    /// Minimum increase of the duration, in percent of the base duration, for a test to be slower.
+    pub slower_threshold_percent: f64,
+    /// Minimum increase of the duration in milliseconds for a test to be slower.
+    pub slower_min_ms: u64,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            base: String::new(),
+            head: String::new(),
+            output: None,
+            format: OutputFormat::Text,
+            fail_on: vec![DiffCategory::NewlyFailing],
+            slower_threshold_percent: 50.0,
+            slower_min_ms: 100,
+        }
+    }
+}
+
+/// Latest execution of a test in one of the compared reports.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffExecution {
+    pub status: TestExecutionStatus,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffEntry {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<DiffExecution>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<DiffExecution>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct TestDiff {
+    #[serde(rename = "newlyFailing")]
+    pub newly_failing: Vec<DiffEntry>,
+    #[serde(rename = "newlyPassing")]
+    pub newly_passing: Vec<DiffEntry>,
+    #[serde(rename = "newlySkipped")]
+    pub newly_skipped: Vec<DiffEntry>,
+    pub slower: Vec<DiffEntry>,
+    pub added: Vec<DiffEntry>,
+    pub removed: Vec<DiffEntry>,
+    /// Number of tests found in both reports that did not change.
+    pub unchanged: usize,
+}
+
+/// Compares the head report against the base report, writes the differences and returns
+/// whether none of the `fail_on` categories contains a test.
+pub fn diff_command(options: DiffOptions) -> bool {
+    let base = read_report(&options.base).expect("Failed to read base report");
+    let head = read_report(&options.head).expect("Failed to read head report");
+
+    let diff = diff_reports(&base, &head, &options);
+    let content = match options.format {
+        OutputFormat::Text => format_text(&diff),
+        OutputFormat::Json => format!(
+            "{}\n",
+            serde_json::to_string_pretty(&diff).expect("Failed to serialize the diff")
+        ),
+        OutputFormat::Markdown => format_markdown(&diff),
+    };
+    write_output(&content, options.output.as_deref()).expect("Failed to write the diff");
+
+    options
+        .fail_on
+        .iter()
+        .all(|category| diff.entries(*category).is_empty())
+}
+
+/// Matches the tests of both reports by path and name and classifies the changes of their
+/// latest executions.
+pub fn diff_reports(base: &TestReport, head: &TestReport, options: &DiffOptions) -> TestDiff {
+    let mut base_tests: HashMap<(&str, &str), &TestReportTest> = HashMap::new();
+    for test in &base.tests {
+        base_tests.entry(test_key(test)).or_insert(test);
+    }
+
+    let mut diff = TestDiff::default();
+    for head_test in &head.tests {
+        let Some(base_test) = base_tests.get(&test_key(head_test)) else {
+            diff.added
+                .push(diff_entry(head_test, None, latest(head_test)));
+            continue;
+        };
+
+        let (Some(base_execution), Some(head_execution)) = (latest(base_test), latest(head_test))
+        else {
+            diff.unchanged += 1;
+            continue;
+        };
+
+        let category = classify(&base_execution, &head_execution, options);
+        let entry = diff_entry(head_test, Some(base_execution), Some(head_execution));
+        match category {
+            Some(DiffCategory::NewlyFailing) => diff.newly_failing.push(entry),
+            Some(DiffCategory::NewlyPassing) => diff.newly_passing.push(entry),
+            Some(DiffCategory::NewlySkipped) => diff.newly_skipped.push(entry),
+            Some(DiffCategory::Slower) => diff.slower.push(entry),
+            Some(DiffCategory::Added | DiffCategory::Removed) | None => diff.unchanged += 1,
+        }
+    }
+
+    let head_keys: HashSet<(&str, &str)> = head.tests.iter().map(test_key).collect();
+    for base_test in &base.tests {
+        if !head_keys.contains(&test_key(base_test)) {
+            diff.removed
+                .push(diff_entry(base_test, latest(base_test), None));
+        }
+    }
+
+    diff
+}
+
+fn test_key(test: &TestReportTest) -> (&str, &str) {
+    (test.path.as_str(), test.name.as_str())
+}
+
+fn classify(
+    base: &DiffExecution,
+    head: &DiffExecution,
+    options: &DiffOptions,
+) -> Option<DiffCategory> {
+    if head.status.is_failure() && !base.status.is_failure() {
+        return Some(DiffCategory::NewlyFailing);
+    }
+    if head.status == TestExecutionStatus::Passed && base.status.is_failure() {
+        return Some(DiffCategory::NewlyPassing);
+    }
+    if head.status == TestExecutionStatus::Skipped && base.status != TestExecutionStatus::Skipped {
+        return Some(DiffCategory::NewlySkipped);
+    }
+
+    let both_passed =
+        base.status == TestExecutionStatus::Passed && head.status == TestExecutionStatus::Passed;
+    let increase = head.duration_ms.saturating_sub(base.duration_ms);
+    let slower = both_passed
+        && increase > 0
+        && increase >= options.slower_min_ms
+        && increase as f64 * 100.0 >= base.duration_ms as f64 * options.slower_threshold_percent;
+    slower.then_some(DiffCategory::Slower)
+}
+
+fn latest(test: &TestReportTest) -> Option<DiffExecution> {
+    test.latest_execution().map(|execution| DiffExecution {
+        status: execution.status.clone(),
+        duration_ms: execution.duration_ms,
+        message: execution.message.clone(),
+    })
+}
+
+fn diff_entry(
+    test: &TestReportTest,
+    base: Option<DiffExecution>,
+    head: Option<DiffExecution>,
+) -> DiffEntry {
+    DiffEntry {
+        id: test.id.clone(),
+        name: test.name.clone(),
+        path: test.path.clone(),
+        base,
+        head,
+    }
+}
+
+impl TestDiff {
+    pub fn entries(&self, category: DiffCategory) -> &[DiffEntry] {
+        match category {
+            DiffCategory::NewlyFailing => &self.newly_failing,
+            DiffCategory::NewlyPassing => &self.newly_passing,
+            DiffCategory::NewlySkipped => &self.newly_skipped,
+            DiffCategory::Slower => &self.slower,
+            DiffCategory::Added => &self.added,
+            DiffCategory::Removed => &self.removed,
+        }
+    }
+
+    fn categories(&self) -> impl Iterator<Item = (&'static str, &[DiffEntry])> {
+        [
+            ("Newly failing", DiffCategory::NewlyFailing),
+            ("Newly passing", DiffCategory::NewlyPassing),
+            ("Newly skipped", DiffCategory::NewlySkipped),
+            ("Slower", DiffCategory::Slower),
+            ("Added", DiffCategory::Added),
+            ("Removed", DiffCategory::Removed),
+        ]
+        .into_iter()
+        .map(|(label, category)| (label, self.entries(category)))
+    }
+}
+
+fn execution_label(execution: Option<&DiffExecution>) -> String {
+    match execution {
+        Some(execution) => format!(
+            "{} ({} ms)",
+            execution.status.as_str(),
+            execution.duration_ms
+        ),
+        None => "-".to_string(),
+    }
+}
+
+fn format_text(diff: &TestDiff) -> String {
+    let mut text = String::new();
+    for (label, entries) in diff.categories() {
+        if entries.is_empty() {
+            continue;
+        }
+        text.push_str(&format!("{} ({}):\n", label, entries.len()));
+        for entry in entries {
+            text.push_str(&format!(
+                "  {}: {} -> {}\n",
+                test_label(&entry.path, &entry.name),
+                execution_label(entry.base.as_ref()),
+                execution_label(entry.head.as_ref())
+            ));
+        }
+    }
+    if text.is_empty() {
+        text.push_str("No changes.\n");
+    }
+    text.push_str(&format!("Unchanged: {}\n", diff.unchanged));
+    text
+}
+
+fn format_markdown(diff: &TestDiff) -> String {
+    let mut markdown = String::from("## Test changes\n\n| Change | Tests |\n| --- | ---: |\n");
+    for (label, entries) in diff.categories() {
+        markdown.push_str(&format!("| {} | {} |\n", label, entries.len()));
+    }
+    markdown.push_str(&format!("| Unchanged | {} |\n", diff.unchanged));
+
+    for (label, entries) in diff.categories() {
+        if entries.is_empty() {
+            continue;
+        }
+        markdown.push_str(&format!(
+            "\n### {}\n\n| Test | Base | Head |\n| --- | --- | --- |\n",
+            label
+        ));
+        for entry in entries {
+            markdown.push_str(&format!(
+                "| {} | {} | {} |\n",
+                escape_markdown_cell(&test_label(&entry.path, &entry.name)),
+                execution_label(entry.base.as_ref()),
+                execution_label(entry.head.as_ref())
+            ));
+        }
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::create_report;
+    use crate::test_identity::test_id;
+    use crate::test_report::TestExecution;
+    use std::fs;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn create_test(name: &str, status: TestExecutionStatus, duration_ms: u64) -> TestReportTest {
+        TestReportTest {
+            id: test_id("suite", name),
+            name: name.to_string(),
+            path: "suite".to_string(),
+            executions: vec![create_execution(
+                "2024-01-01T00:00:00Z",
+                status,
+                duration_ms,
+            )],
+            ..Default::default()
+        }
+    }
+
+    fn create_execution(
+        timestamp: &str,
+        status: TestExecutionStatus,
+        duration_ms: u64,
+    ) -> TestExecution {
+        TestExecution {
+            timestamp: timestamp.to_string(),
+            status,
+            duration_ms,
+            ..Default::default()
+        }
+    }
+
+    fn names(entries: &[DiffEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    fn write_temp_report(report: &TestReport) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(serde_json::to_string(report).unwrap().as_bytes())
+            .unwrap();
+        file
+    }
+
+    #[test]
+    fn tests_are_classified_by_change() {
+        let base = create_report(vec![
+            create_test("breaks", TestExecutionStatus::Passed, 10),
+            create_test("errors", TestExecutionStatus::Skipped, 10),
+            create_test("fixed", TestExecutionStatus::Failed, 10),
+            create_test("disabled", TestExecutionStatus::Passed, 10),
+            create_test("slows_down", TestExecutionStatus::Passed, 200),
+            create_test("same", TestExecutionStatus::Failed, 10),
+            create_test("deleted", TestExecutionStatus::Passed, 10),
+        ]);
+        let head = create_report(vec![
+            create_test("breaks", TestExecutionStatus::Failed, 10),
+            create_test("errors", TestExecutionStatus::Error, 10),
+            create_test("fixed", TestExecutionStatus::Passed, 10),
+            create_test("disabled", TestExecutionStatus::Skipped, 0),
+            create_test("slows_down", TestExecutionStatus::Passed, 8000),
+            create_test("same", TestExecutionStatus::Failed, 10),
+            create_test("new", TestExecutionStatus::Passed, 10),
+        ]);
+
+        let diff = diff_reports(&base, &head, &DiffOptions::default());
+
+        assert_eq!(names(&diff.newly_failing), vec!["breaks", "errors"]);
+        assert_eq!(names(&diff.newly_passing), vec!["fixed"]);
+        assert_eq!(names(&diff.newly_skipped), vec!["disabled"]);
+        assert_eq!(names(&diff.slower), vec!["slows_down"]);
+        assert_eq!(names(&diff.added), vec!["new"]);
+        assert_eq!(names(&diff.removed), vec!["deleted"]);
+        assert_eq!(diff.unchanged, 1);
+    }
+
+    #[test]
+    fn small_duration_increases_are_not_slower() {
+        let base = create_report(vec![
+            create_test("relative", TestExecutionStatus::Passed, 1000),
+            create_test("absolute", TestExecutionStatus::Passed, 10),
+        ]);
+        let head = create_report(vec![
+            create_test("relative", TestExecutionStatus::Passed, 1400),
+            create_test("absolute", TestExecutionStatus::Passed, 90),
+        ]);
+
+        let diff = diff_reports(&base, &head, &DiffOptions::default());
+
+        assert!(diff.slower.is_empty());
+        assert_eq!(diff.unchanged, 2);
+    }
+
+    #[test]
+    fn latest_execution_is_compared() {
+        let mut base_test = create_test("test", TestExecutionStatus::Failed, 10);
+        base_test.executions.push(create_execution(
+            "2024-01-02T00:00:00Z",
+            TestExecutionStatus::Passed,
+            10,
+        ));
+        let mut head_test = create_test("test", TestExecutionStatus::Passed, 10);
+        head_test.executions.insert(
+            0,
+            create_execution("2024-01-03T00:00:00Z", TestExecutionStatus::Failed, 10),
+        );
+
+        let diff = diff_reports(
+            &create_report(vec![base_test]),
+            &create_report(vec![head_test]),
+            &DiffOptions::default(),
+        );
+
+        assert_eq!(names(&diff.newly_failing), vec!["test"]);
+    }
+
+    #[test]
+    fn diff_command_fails_only_on_selected_categories() {
+        let base_file = write_temp_report(&create_report(vec![create_test(
+            "test",
+            TestExecutionStatus::Passed,
+            10,
+        )]));
+        let head_file = write_temp_report(&create_report(vec![
+            create_test("test", TestExecutionStatus::Passed, 10),
+            create_test("new", TestExecutionStatus::Passed, 10),
+        ]));
+        let output_file = NamedTempFile::new().unwrap();
+        let options = DiffOptions {
+            base: base_file.path().to_str().unwrap().to_string(),
+            head: head_file.path().to_str().unwrap().to_string(),
+            output: Some(output_file.path().to_str().unwrap().to_string()),
+            format: OutputFormat::Json,
+            ..Default::default()
+        };
+
+        assert!(diff_command(options.clone()));
+        assert!(!diff_command(DiffOptions {
+            fail_on: vec![DiffCategory::Added],
+            ..options
+        }));
+
+        let result: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(output_file.path()).unwrap()).unwrap();
+        assert_eq!(result["added"][0]["name"], "new");
+        assert_eq!(result["added"][0]["head"]["status"], "PASSED");
+        assert_eq!(result["unchanged"], 1);
+    }
+
+    #[test]
+    fn text_and_markdown_list_changed_tests() {
+        let diff = diff_reports(
+            &create_report(vec![create_test("test", TestExecutionStatus::Passed, 10)]),
+            &create_report(vec![create_test("test", TestExecutionStatus::Failed, 12)]),
+            &DiffOptions::default(),
+        );
+
+        assert_eq!(
+            format_text(&diff),
+            "Newly failing (1):\n  suite > test: PASSED (10 ms) -> FAILED (12 ms)\nUnchanged: 0\n"
+        );
+        assert!(
+            format_markdown(&diff).contains(
+                "### Newly failing\n\n| Test | Base | Head |\n| --- | --- | --- |\n| suite > test | PASSED (10 ms) | FAILED (12 ms) |\n"
+            )
+        );
+    }
+
+    #[test]
+    fn text_without_changes_says_so() {
+        let report = create_report(vec![create_test("test", TestExecutionStatus::Passed, 10)]);
+
+        let diff = diff_reports(&report, &report, &DiffOptions::default());
+
+        assert_eq!(format_text(&diff), "No changes.\nUnchanged: 1\n");
+    }
+}
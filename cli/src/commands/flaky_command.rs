@@ -0,0 +1,361 @@
+use crate::output_format::{OutputFormat, escape_markdown_cell, test_label};
+use crate::report_io::{read_report, write_output, write_report};
+use crate::tag_rules::add_tags_to_test;
+use crate::test_report::{TestExecution, TestExecutionStatus, TestReport, TestReportTest};
+use crate::timestamps::compare_timestamps;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct FlakyOptions {
+    pub input: String,
+    pub output: Option<String>,
+    pub format: OutputFormat,
+    /// Number of most recent executions of each test to analyse.
+    pub window: usize,
+    /// Minimum share of consecutive executions that changed between passing and failing.
+    pub min_flip_rate: f64,
+    /// Tag to add to the flaky tests of the report.
+    pub tag: Option<String>,
+    /// Path of the tagged report, defaults to overwriting the input.
+    pub tagged_output: Option<String>,
+}
+
+impl Default for FlakyOptions {
+    fn default() -> Self {
+        FlakyOptions {
+            input: String::new(),
+            output: None,
+            format: OutputFormat::Text,
+            window: 20,
+            min_flip_rate: 0.1,
+            tag: None,
+            tagged_output: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FlakyTest {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    /// Number of analysed executions, skipped executions excluded.
+    pub executions: usize,
+    /// Number of consecutive executions that changed between passing and failing.
+    pub flips: usize,
+    #[serde(rename = "flipRate")]
+    pub flip_rate: f64,
+    /// Number of runs in which the test both failed and passed, i.e. passed on retry.
+    #[serde(rename = "retryFlakes")]
+    pub retry_flakes: usize,
+    /// Timestamp of the most recent execution that flipped or passed on retry.
+    #[serde(rename = "lastFlake", skip_serializing_if = "Option::is_none")]
+    pub last_flake: Option<String>,
+}
+
+pub fn flaky_command(options: FlakyOptions) {
+    let mut report = read_report(&options.input).expect("Failed to read input file");
+
+    let flaky_tests = find_flaky_tests(&report, options.window, options.min_flip_rate);
+    let content = match options.format {
+        OutputFormat::Text => format_text(&flaky_tests),
+        OutputFormat::Json => format!(
+            "{}\n",
+            serde_json::to_string_pretty(&flaky_tests).expect("Failed to serialize flaky tests")
+        ),
+        OutputFormat::Markdown => format_markdown(&flaky_tests),
+    };
+    write_output(&content, options.output.as_deref()).expect("Failed to write flaky tests");
+
+    if let Some(tag) = options.tag {
+        tag_flaky_tests(&mut report, &flaky_tests, &tag);
+        let tagged_output = options.tagged_output.unwrap_or(options.input);
+        write_report(&report, &tagged_output).expect("Failed to write report");
+    }
+}
+
+/// Scores every test by how often its recent executions flipped between passing and failing,
+/// and by the runs in which it passed on retry. Flaky tests are ranked by flip rate.
+pub fn find_flaky_tests(report: &TestReport, window: usize, min_flip_rate: f64) -> Vec<FlakyTest> {
+    let mut flaky_tests: Vec<FlakyTest> = executions_by_test(report)
+        .into_iter()
+        .map(|(test, executions)| score_test(test, executions, window))
+        .filter(|flaky| {
+            flaky.retry_flakes > 0 || (flaky.flips > 0 && flaky.flip_rate >= min_flip_rate)
+        })
+        .collect();
+
+    flaky_tests.sort_by(|a, b| {
+        b.flip_rate
+            .total_cmp(&a.flip_rate)
+            .then(b.retry_flakes.cmp(&a.retry_flakes))
+            .then(b.last_flake.cmp(&a.last_flake))
+    });
+    flaky_tests
+}
+
+/// Collects the executions of every test id. A parsed report has one test per test case, so
+/// the retries of a test in a run are separate tests with the same id.
+fn executions_by_test(report: &TestReport) -> Vec<(&TestReportTest, Vec<&TestExecution>)> {
+    let mut indexes: HashMap<&str, usize> = HashMap::new();
+    let mut tests: Vec<(&TestReportTest, Vec<&TestExecution>)> = Vec::new();
+    for test in &report.tests {
+        let index = *indexes.entry(test.id.as_str()).or_insert_with(|| {
+            tests.push((test, Vec::new()));
+            tests.len() - 1
+        });
+        tests[index].1.extend(&test.executions);
+    }
+    tests
+}
+
+fn score_test(
+    test: &TestReportTest,
+    mut executions: Vec<&TestExecution>,
+    window: usize,
+) -> FlakyTest {
+    executions.retain(|execution| execution.status != TestExecutionStatus::Skipped);
+    executions.sort_by(|a, b| compare_timestamps(&a.timestamp, &b.timestamp));
+    let executions = &executions[executions.len().saturating_sub(window)..];
+
+    let mut last_flake: Option<&str> = None;
+    let mut flips = 0;
+    for pair in executions.windows(2) {
+        if pair[0].status.is_failure() != pair[1].status.is_failure() {
+            flips += 1;
+            last_flake = later_timestamp(last_flake, &pair[1].timestamp);
+        }
+    }
+
+    let mut runs: HashMap<&str, Vec<&TestExecution>> = HashMap::new();
+    for execution in executions {
+        if let Some(run_id) = &execution.run_id {
+            runs.entry(run_id.as_str()).or_default().push(execution);
+        }
+    }
+    let mut retry_flakes = 0;
+    for run_executions in runs.values() {
+        let failed = run_executions
+            .iter()
+            .any(|execution| execution.status.is_failure());
+        let passed = run_executions
+            .iter()
+            .any(|execution| execution.status == TestExecutionStatus::Passed);
+        if failed && passed {
+            retry_flakes += 1;
+            for execution in run_executions {
+                last_flake = later_timestamp(last_flake, &execution.timestamp);
+            }
+        }
+    }
+
+    FlakyTest {
+        id: test.id.clone(),
+        name: test.name.clone(),
+        path: test.path.clone(),
+        executions: executions.len(),
+        flips,
+        flip_rate: if executions.len() < 2 {
+            0.0
+        } else {
+            flips as f64 / (executions.len() - 1) as f64
+        },
+        retry_flakes,
+        last_flake: last_flake.map(|timestamp| timestamp.to_string()),
+    }
+}
+
+fn later_timestamp<'a>(current: Option<&'a str>, timestamp: &'a str) -> Option<&'a str> {
+    match current {
+        Some(current) if compare_timestamps(current, timestamp).is_ge() => Some(current),
+        _ => Some(timestamp),
+    }
+}
+
+fn tag_flaky_tests(report: &mut TestReport, flaky_tests: &[FlakyTest], tag: &str) {
+    let tags = [tag.to_string()];
+    for test in &mut report.tests {
+        if flaky_tests.iter().any(|flaky| flaky.id == test.id) {
+            test.tags = add_tags_to_test(test.tags.as_ref(), &tags);
+        }
+    }
+}
+
+fn format_text(flaky_tests: &[FlakyTest]) -> String {
+    if flaky_tests.is_empty() {
+        return "No flaky tests.\n".to_string();
+    }
+
+    let mut text = format!("Flaky tests ({}):\n", flaky_tests.len());
+    for flaky in flaky_tests {
+        text.push_str(&format!(
+            "  {}: {} flips in {} executions ({:.0}%), {} passed on retry, last flake {}\n",
+            test_label(&flaky.path, &flaky.name),
+            flaky.flips,
+            flaky.executions,
+            flaky.flip_rate * 100.0,
+            flaky.retry_flakes,
+            flaky.last_flake.as_deref().unwrap_or("-")
+        ));
+    }
+    text
+}
+
+fn format_markdown(flaky_tests: &[FlakyTest]) -> String {
+    let mut markdown = String::from(
+        "## Flaky tests\n\n| Test | Flips | Executions | Flip rate | Passed on retry | Last flake |\n\
+         | --- | ---: | ---: | ---: | ---: | --- |\n",
+    );
+    for flaky in flaky_tests {
+        markdown.push_str(&format!(
+            "| {} | {} | {} | {:.0}% | {} | {} |\n",
+            escape_markdown_cell(&test_label(&flaky.path, &flaky.name)),
+            flaky.flips,
+            flaky.executions,
+            flaky.flip_rate * 100.0,
+            flaky.retry_flakes,
+            flaky.last_flake.as_deref().unwrap_or("-")
+        ));
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::create_report;
+    use TestExecutionStatus::{Failed, Passed, Skipped};
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn create_execution(day: u32, status: TestExecutionStatus, run_id: &str) -> TestExecution {
+        TestExecution {
+            timestamp: format!("2024-01-{:02}T00:00:00Z", day),
+            status,
+            duration_ms: 100,
+            run_id: Some(run_id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn create_test(name: &str, statuses: &[TestExecutionStatus]) -> TestReportTest {
+        TestReportTest {
+            id: name.to_string(),
+            name: name.to_string(),
+            path: "suite".to_string(),
+            executions: statuses
+                .iter()
+                .enumerate()
+                .map(|(index, status)| {
+                    create_execution(index as u32 + 1, status.clone(), &format!("run-{}", index))
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn tests_are_ranked_by_flip_rate() {
+        let report = create_report(vec![
+            create_test("stable", &[Passed, Passed, Passed, Passed]),
+            create_test("broken", &[Passed, Passed, Failed, Failed]),
+            create_test("sometimes", &[Passed, Failed, Passed, Passed]),
+            create_test("always", &[Passed, Failed, Passed, Failed]),
+        ]);
+
+        let flaky_tests = find_flaky_tests(&report, 20, 0.5);
+
+        let scores: Vec<(&str, usize, f64)> = flaky_tests
+            .iter()
+            .map(|flaky| (flaky.name.as_str(), flaky.flips, flaky.flip_rate))
+            .collect();
+        assert_eq!(
+            scores,
+            vec![("always", 3, 1.0), ("sometimes", 2, 2.0 / 3.0)]
+        );
+        assert_eq!(
+            flaky_tests[0].last_flake.as_deref(),
+            Some("2024-01-04T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn skipped_and_old_executions_are_ignored() {
+        let report = create_report(vec![create_test(
+            "test",
+            &[Failed, Passed, Failed, Passed, Skipped, Passed, Passed],
+        )]);
+
+        let flaky_tests = find_flaky_tests(&report, 3, 0.1);
+
+        assert!(flaky_tests.is_empty());
+    }
+
+    #[test]
+    fn passing_on_retry_is_flaky() {
+        let mut test = create_test("test", &[Passed]);
+        test.executions.extend([
+            create_execution(2, Failed, "run-1"),
+            create_execution(3, Passed, "run-1"),
+        ]);
+
+        let flaky_tests = find_flaky_tests(&create_report(vec![test]), 20, 1.0);
+
+        assert_eq!(flaky_tests.len(), 1);
+        assert_eq!(flaky_tests[0].retry_flakes, 1);
+        assert_eq!(
+            flaky_tests[0].last_flake.as_deref(),
+            Some("2024-01-03T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn retries_parsed_as_separate_tests_are_flaky() {
+        let mut retry = create_test("test", &[Passed]);
+        retry.executions[0].timestamp = "2024-01-01T00:00:01Z".to_string();
+        let report = create_report(vec![
+            create_test("test", &[Failed]),
+            create_test("other", &[Passed]),
+            retry,
+        ]);
+
+        let flaky_tests = find_flaky_tests(&report, 20, 1.0);
+
+        assert_eq!(flaky_tests.len(), 1);
+        assert_eq!(flaky_tests[0].id, "test");
+        assert_eq!(flaky_tests[0].executions, 2);
+        assert_eq!(flaky_tests[0].retry_flakes, 1);
+    }
+
+    #[test]
+    fn flaky_command_tags_flaky_tests() {
+        let mut input_file = NamedTempFile::new().unwrap();
+        let report = create_report(vec![
+            create_test("stable", &[Passed, Passed]),
+            create_test("flaky", &[Passed, Failed, Passed]),
+        ]);
+        input_file
+            .write_all(serde_json::to_string(&report).unwrap().as_bytes())
+            .unwrap();
+        let output_file = NamedTempFile::new().unwrap();
+        let tagged_file = NamedTempFile::new().unwrap();
+
+        flaky_command(FlakyOptions {
+            input: input_file.path().to_str().unwrap().to_string(),
+            output: Some(output_file.path().to_str().unwrap().to_string()),
+            tag: Some("flaky".to_string()),
+            tagged_output: Some(tagged_file.path().to_str().unwrap().to_string()),
+            ..Default::default()
+        });
+
+        let tagged = read_report(tagged_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(tagged.tests[0].tags, None);
+        assert_eq!(tagged.tests[1].tags, Some(vec!["flaky".to_string()]));
+        assert_eq!(
+            std::fs::read_to_string(output_file.path()).unwrap(),
+            "Flaky tests (1):\n  suite > flaky: 2 flips in 3 executions (100%), 0 passed on retry, \
+             last flake 2024-01-03T00:00:00Z\n"
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use crate::commands::diff_command::{DiffOptions, diff_reports};
+use crate::output_format::test_label;
+use crate::report_io::read_report;
+use crate::report_stats::{StatusCounts, errored_suites, total_duration_ms};
+use crate::test_report::TestReport;
+
+#[derive(Debug, Clone, Default)]
+pub struct GateOptions {
+    pub input: String,
+    pub max_failures: Option<u64>,
+    /// Minimum percentage of the tests that ran that must pass.
+    pub min_pass_rate: Option<f64>,
+    /// Tags of tests that must not fail.
+    pub no_failures_tagged: Vec<String>,
+    pub max_duration_ms: Option<u64>,
+    /// Report that the tests must not newly fail against.
+    pub baseline: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleResult {
+    pub rule: String,
+    pub passed: bool,
+    pub details: String,
+}
+
+/// Evaluates the rules against the report, prints the verdict and returns whether every rule
+/// passed.
+pub fn gate_command(options: GateOptions) -> bool {
+    let report = read_report(&options.input).expect("Failed to read input file");
+    let baseline = options
+        .baseline
+        .as_ref()
+        .map(|baseline| read_report(baseline).expect("Failed to read baseline report"));
+
+    let results = evaluate_gate(&report, baseline.as_ref(), &options);
+    for result in &results {
+        let verdict = if result.passed { "PASSED" } else { "FAILED" };
+        println!("{} {}: {}", verdict, result.rule, result.details);
+    }
+
+    let violated = results.iter().filter(|result| !result.passed).count();
+    if violated == 0 {
+        println!("Gate passed");
+    } else {
+        println!(
+            "Gate failed: {} of {} rules violated",
+            violated,
+            results.len()
+        );
+    }
+    violated == 0
+}
+
+pub fn evaluate_gate(
+    report: &TestReport,
+    baseline: Option<&TestReport>,
+    options: &GateOptions,
+) -> Vec<RuleResult> {
+    let counts = StatusCounts::from_tests(&report.tests);
+    let errored_suites: Vec<String> = errored_suites(&report.suites)
+        .into_iter()
+        .map(|suite| suite.name.clone())
+        .collect();
+    let suite_errors = errored_suites.len() as u64;
+    let mut results = Vec::new();
+
+    if let Some(max_failures) = options.max_failures {
+        let mut details = format!("{} failed tests", counts.failures());
+        if !errored_suites.is_empty() {
+            details.push_str(&format!(
+                ", {} errored suites: {}",
+                suite_errors,
+                errored_suites.join(", ")
+            ));
+        }
+        results.push(RuleResult {
+            rule: format!("at most {} failures", max_failures),
+            passed: counts.failures() + suite_errors <= max_failures,
+            details,
+        });
+    }
+
+    if let Some(min_pass_rate) = options.min_pass_rate {
+        let pass_rate = counts.pass_rate(suite_errors);
+        let mut details = match pass_rate {
+            Some(pass_rate) => format!(
+                "{:.1}% of {} tests passed",
+                pass_rate,
+                counts.passed + counts.failures()
+            ),
+            None => "no tests ran".to_string(),
+        };
+        if suite_errors > 0 {
+            details.push_str(&format!(
+                ", {} errored suites count as failed",
+                suite_errors
+            ));
+        }
+        results.push(RuleResult {
+            rule: format!("pass rate of at least {}%", min_pass_rate),
+            passed: pass_rate.is_some_and(|pass_rate| pass_rate >= min_pass_rate),
+            details,
+        });
+    }
+
+    for tag in &options.no_failures_tagged {
+        let failing: Vec<String> = report
+            .tests
+            .iter()
+            .filter(|test| test.tags.as_ref().is_some_and(|tags| tags.contains(tag)))
+            .filter(|test| {
+                test.latest_execution()
+                    .is_some_and(|execution| execution.status.is_failure())
+            })
+            .map(|test| test_label(&test.path, &test.name))
+            .collect();
+        results.push(RuleResult {
+            rule: format!("no failures in tests tagged {}", tag),
+            passed: failing.is_empty(),
+            details: failing_details(&failing),
+        });
+    }
+
+    if let Some(max_duration_ms) = options.max_duration_ms {
+        let duration_ms = total_duration_ms(&report.tests);
+        results.push(RuleResult {
+            rule: format!("total duration of at most {} ms", max_duration_ms),
+            passed: duration_ms <= max_duration_ms,
+            details: format!("tests took {} ms", duration_ms),
+        });
+    }
+
+    if let Some(baseline) = baseline {
+        let diff = diff_reports(baseline, report, &DiffOptions::default());
+        let added_failures = diff.added.iter().filter(|entry| {
+            entry
+                .head
+                .as_ref()
+                .is_some_and(|head| head.status.is_failure())
+        });
+        let new_failures: Vec<String> = diff
+            .newly_failing
+            .iter()
+            .chain(added_failures)
+            .map(|entry| test_label(&entry.path, &entry.name))
+            .collect();
+        results.push(RuleResult {
+            rule: "no new failures compared to the baseline".to_string(),
+            passed: new_failures.is_empty(),
+            details: failing_details(&new_failures),
+        });
+    }
+
+    results
+}
+
+fn failing_details(failing: &[String]) -> String {
+    if failing.is_empty() {
+        "no failed tests".to_string()
+    } else {
+        format!("{} failed tests: {}", failing.len(), failing.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{create_execution, create_report, create_suite};
+    use crate::test_report::{TestExecutionStatus, TestReportTest};
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn create_test(name: &str, status: TestExecutionStatus, tags: Vec<&str>) -> TestReportTest {
+        TestReportTest {
+            id: name.to_string(),
+            name: name.to_string(),
+            path: "suite".to_string(),
+            executions: vec![create_execution(status)],
+            tags: (!tags.is_empty()).then(|| tags.into_iter().map(|s| s.to_string()).collect()),
+            ..Default::default()
+        }
+    }
+
+    fn verdicts(results: &[RuleResult]) -> Vec<(&str, bool)> {
+        results
+            .iter()
+            .map(|result| (result.rule.as_str(), result.passed))
+            .collect()
+    }
+
+    fn report() -> TestReport {
+        create_report(vec![
+            create_test("login", TestExecutionStatus::Failed, vec!["critical"]),
+            create_test("search", TestExecutionStatus::Passed, vec!["critical"]),
+            create_test("export", TestExecutionStatus::Error, vec![]),
+            create_test("import", TestExecutionStatus::Passed, vec![]),
+            create_test("print", TestExecutionStatus::Skipped, vec![]),
+        ])
+    }
+
+    #[test]
+    fn rules_are_evaluated_against_the_latest_executions() {
+        let results = evaluate_gate(
+            &report(),
+            None,
+            &GateOptions {
+                max_failures: Some(2),
+                min_pass_rate: Some(60.0),
+                no_failures_tagged: vec!["critical".to_string(), "smoke".to_string()],
+                max_duration_ms: Some(400),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            verdicts(&results),
+            vec![
+                ("at most 2 failures", true),
+                ("pass rate of at least 60%", false),
+                ("no failures in tests tagged critical", false),
+                ("no failures in tests tagged smoke", true),
+                ("total duration of at most 400 ms", false),
+            ]
+        );
+        assert_eq!(results[1].details, "50.0% of 4 tests passed");
+        assert_eq!(results[2].details, "1 failed tests: suite > login");
+        assert_eq!(results[4].details, "tests took 500 ms");
+    }
+
+    #[test]
+    fn new_failures_include_added_failing_tests() {
+        let baseline = create_report(vec![
+            create_test("login", TestExecutionStatus::Failed, vec![]),
+            create_test("export", TestExecutionStatus::Passed, vec![]),
+        ]);
+
+        let results = evaluate_gate(&report(), Some(&baseline), &GateOptions::default());
+
+        assert_eq!(
+            verdicts(&results),
+            vec![("no new failures compared to the baseline", false)]
+        );
+        assert_eq!(results[0].details, "1 failed tests: suite > export");
+    }
+
+    #[test]
+    fn errored_suites_are_failures() {
+        let mut report = create_report(Vec::new());
+        report.suites = vec![
+            create_suite(
+                "com.example.SetupTest",
+                "2024-01-01T00:00:00Z",
+                &["Suite declares 1 errors but its test cases report 0"],
+            ),
+            create_suite("com.example.OtherTest", "2024-01-01T00:00:00Z", &[]),
+        ];
+        let options = GateOptions {
+            max_failures: Some(0),
+            min_pass_rate: Some(100.0),
+            ..Default::default()
+        };
+
+        let results = evaluate_gate(&report, None, &options);
+
+        assert_eq!(
+            verdicts(&results),
+            vec![
+                ("at most 0 failures", false),
+                ("pass rate of at least 100%", false),
+            ]
+        );
+        assert_eq!(
+            results[0].details,
+            "0 failed tests, 1 errored suites: com.example.SetupTest"
+        );
+        assert_eq!(
+            results[1].details,
+            "0.0% of 0 tests passed, 1 errored suites count as failed"
+        );
+    }
+
+    #[test]
+    fn pass_rate_rule_fails_when_no_test_ran() {
+        let report = create_report(vec![create_test(
+            "print",
+            TestExecutionStatus::Skipped,
+            vec![],
+        )]);
+
+        let results = evaluate_gate(
+            &report,
+            None,
+            &GateOptions {
+                min_pass_rate: Some(0.0),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            verdicts(&results),
+            vec![("pass rate of at least 0%", false)]
+        );
+        assert_eq!(results[0].details, "no tests ran");
+    }
+
+    #[test]
+    fn gate_command_returns_whether_every_rule_passed() {
+        let mut input_file = NamedTempFile::new().unwrap();
+        input_file
+            .write_all(serde_json::to_string(&report()).unwrap().as_bytes())
+            .unwrap();
+        let input = input_file.path().to_str().unwrap().to_string();
+
+        assert!(gate_command(GateOptions {
+            input: input.clone(),
+            max_failures: Some(2),
+            ..Default::default()
+        }));
+        assert!(!gate_command(GateOptions {
+            input,
+            max_failures: Some(1),
+            ..Default::default()
+        }));
+    }
+}
@@ -0,0 +1,90 @@
+use crate::parser_registry::{AUTO_REPORT_TYPE, ParserRegistry};
+use crate::parsers::external::parser::discover_plugins;
+use std::env;
+use std::path::PathBuf;
+
+pub fn list_formats_command() {
+    let registry = ParserRegistry::with_builtin_parsers();
+    let plugins = env::var_os("PATH")
+        .map(|path_variable| discover_plugins(&path_variable))
+        .unwrap_or_default();
+
+    print!("{}", format_formats(&registry, &plugins));
+}
+
+fn format_formats(registry: &ParserRegistry, plugins: &[(String, PathBuf)]) -> String {
+    let mut rows: Vec<[String; 3]> = vec![[
+        "NAME".to_string(),
+        "EXTENSIONS".to_string(),
+        "DESCRIPTION".to_string(),
+    ]];
+
+    for registration in registry.registrations() {
+        rows.push([
+            registration.name.clone(),
+            format_extensions(&registration.file_extensions),
+            registration.description.clone(),
+        ]);
+    }
+
+    rows.push([
+        AUTO_REPORT_TYPE.to_string(),
+        format_extensions(&registry.file_extensions(AUTO_REPORT_TYPE)),
+        "Detect the format of each file from its content".to_string(),
+    ]);
+
+    for (name, executable) in plugins {
+        rows.push([
+            name.clone(),
+            "-".to_string(),
+            format!("Parser plugin {}", executable.display()),
+        ]);
+    }
+
+    let name_width = rows.iter().map(|row| row[0].len()).max().unwrap_or(0);
+    let extensions_width = rows.iter().map(|row| row[1].len()).max().unwrap_or(0);
+
+    rows.iter()
+        .map(|[name, extensions, description]| {
+            format!(
+                "{:<name_width$}  {:<extensions_width$}  {}\n",
+                name, extensions, description
+            )
+        })
+        .collect()
+}
+
+fn format_extensions<S: AsRef<str>>(extensions: &[S]) -> String {
+    if extensions.is_empty() {
+        return "-".to_string();
+    }
+
+    extensions
+        .iter()
+        .map(|extension| format!(".{}", extension.as_ref()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_builtin_formats_auto_detection_and_plugins() {
+        let registry = ParserRegistry::with_builtin_parsers();
+        let plugins = vec![(
+            "foo".to_string(),
+            PathBuf::from("/usr/bin/test-prism-parser-foo"),
+        )];
+
+        let result = format_formats(&registry, &plugins);
+
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("NAME   EXTENSIONS  DESCRIPTION"));
+        assert!(lines[1].starts_with("junit  .xml        JUnit XML reports"));
+        assert!(lines[2].starts_with("auto   .xml        Detect the format"));
+        assert!(lines[3].starts_with("foo    -           Parser plugin"));
+    }
+}
@@ -0,0 +1,871 @@
+use crate::report_io::{read_report, write_report};
+use crate::test_report::{REPORT_VERSION, TestExecution, TestReport, TestReportTest, TestRun};
+use crate::timestamps::{compare_timestamps, parse_timestamp};
+use chrono::{DateTime, Duration, Utc};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+#[derive(Debug, Clone, Default)]
+pub struct Retention {
+    pub max_runs: Option<usize>,
+    pub max_age_days: Option<u64>,
+}
+
+pub fn merge_command(
+    history: String,
+    inputs: Vec<String>,
+    output: Option<String>,
+    retention: Retention,
+) {
+    let history_report = if Path::new(&history).exists() {
+        Some(read_report(&history).expect("Failed to read history file"))
+    } else {
+        None
+    };
+    let new_reports: Vec<TestReport> = inputs
+        .iter()
+        .map(|input| read_report(input).expect("Failed to read input file"))
+        .collect();
+
+    let mut merged_report = merge_reports(history_report, new_reports);
+    apply_retention(&mut merged_report, &retention);
+
+    let output_path = output.unwrap_or(history);
+    write_report(&merged_report, &output_path).expect("Failed to write report");
+}
+
+pub fn merge_reports(history: Option<TestReport>, mut new_reports: Vec<TestReport>) -> TestReport {
+    new_reports.sort_by(|a, b| compare_timestamps(&a.timestamp, &b.timestamp));
+
+    let mut merged_report = history.unwrap_or_else(|| TestReport {
+        version: REPORT_VERSION,
+        timestamp: String::new(),
+        runs: Vec::new(),
+        suites: Vec::new(),
+        tests: Vec::new(),
+    });
+    let mut test_indexes: HashMap<String, usize> = merged_report
+        .tests
+        .iter()
+        .enumerate()
+        .map(|(index, test)| (test.id.clone(), index))
+        .collect();
+    // Tests migrated from reports without ids are matched by path and name instead, and take
+    // the id of the first new test that matches.
+    let mut legacy_indexes: HashMap<(String, String), usize> = merged_report
+        .tests
+        .iter()
+        .enumerate()
+        .filter(|(_, test)| test.has_legacy_id())
+        .map(|(index, test)| ((test.path.clone(), test.name.clone()), index))
+        .collect();
+
+    for new_report in new_reports {
+        if compare_timestamps(&new_report.timestamp, &merged_report.timestamp) == Ordering::Greater
+        {
+            merged_report.timestamp = new_report.timestamp;
+        }
+
+        for run in new_report.runs {
+            if !merged_report
+                .runs
+                .iter()
+                .any(|existing| existing.id == run.id)
+            {
+                merged_report.runs.push(run);
+            }
+        }
+
+        for suite in new_report.suites {
+            if !merged_report.suites.contains(&suite) {
+                merged_report.suites.push(suite);
+            }
+        }
+
+        for new_test in new_report.tests {
+            if !test_indexes.contains_key(&new_test.id)
+                && let Some(index) =
+                    legacy_indexes.remove(&(new_test.path.clone(), new_test.name.clone()))
+            {
+                let legacy_test = &mut merged_report.tests[index];
+                test_indexes.remove(&legacy_test.id);
+                legacy_test.id = new_test.id.clone();
+                test_indexes.insert(new_test.id.clone(), index);
+            }
+
+            match test_indexes.get(&new_test.id) {
+                Some(&index) => merge_test(&mut merged_report.tests[index], new_test),
+                None => {
+                    test_indexes.insert(new_test.id.clone(), merged_report.tests.len());
+                    merged_report.tests.push(new_test);
+                }
+            }
+        }
+    }
+
+    merged_report
+        .runs
+        .sort_by(|a, b| compare_timestamps(&a.timestamp, &b.timestamp));
+    merged_report
+        .suites
+        .sort_by(|a, b| compare_timestamps(&a.timestamp, &b.timestamp));
+    for test in &mut merged_report.tests {
+        sort_executions(&mut test.executions);
+    }
+
+    merged_report
+}
+
+/// Keeps the last runs and the runs within the maximum age, both for the report and for the
+/// executions of each test. Executions without a run are grouped by their timestamp.
+pub fn apply_retention(report: &mut TestReport, retention: &Retention) {
+    let oldest_allowed = retention.max_age_days.and_then(|days| {
+        let newest = parse_timestamp(&report.timestamp)?;
+        Some(newest - Duration::days(days as i64))
+    });
+
+    let mut removed_run_ids: HashSet<String> = HashSet::new();
+    if let Some(oldest_allowed) = oldest_allowed {
+        let (old_runs, recent_runs): (Vec<TestRun>, Vec<TestRun>) = report
+            .runs
+            .drain(..)
+            .partition(|run| is_older_than(&run.timestamp, oldest_allowed));
+        removed_run_ids.extend(old_runs.into_iter().map(|run| run.id));
+        report.runs = recent_runs;
+    }
+    if let Some(max_runs) = retention.max_runs
+        && report.runs.len() > max_runs
+    {
+        let excess = report.runs.len() - max_runs;
+        removed_run_ids.extend(report.runs.drain(..excess).map(|run| run.id));
+    }
+
+    report.suites.retain(|suite| {
+        let run_removed = suite
+            .run_id
+            .as_ref()
+            .is_some_and(|run_id| removed_run_ids.contains(run_id));
+        let too_old = oldest_allowed
+            .is_some_and(|oldest_allowed| is_older_than(&suite.timestamp, oldest_allowed));
+        !run_removed && !too_old
+    });
+
+    for test in &mut report.tests {
+        test.executions.retain(|execution| {
+            let run_removed = execution
+                .run_id
+                .as_ref()
+                .is_some_and(|run_id| removed_run_ids.contains(run_id));
+            let too_old = oldest_allowed
+                .is_some_and(|oldest_allowed| is_older_than(&execution.timestamp, oldest_allowed));
+            !run_removed && !too_old
+        });
+
+        if let Some(max_runs) = retention.max_runs {
+            keep_last_runs(&mut test.executions, max_runs);
+        }
+    }
+
+    report.tests.retain(|test| !test.executions.is_empty());
+}
+
+fn keep_last_runs(executions: &mut Vec<TestExecution>, max_runs: usize) {
+    let mut run_keys: Vec<&str> = Vec::new();
+    for execution in executions.iter().rev() {
+        let run_key = execution_run_key(execution);
+        if !run_keys.contains(&run_key) {
+            run_keys.push(run_key);
+        }
+    }
+    if run_keys.len() <= max_runs {
+        return;
+    }
+
+    let kept_run_keys: HashSet<String> = run_keys[..max_runs]
+        .iter()
+        .map(|run_key| run_key.to_string())
+        .collect();
+    executions.retain(|execution| kept_run_keys.contains(execution_run_key(execution)));
+}
+
+fn execution_run_key(execution: &TestExecution) -> &str {
+    execution.run_id.as_deref().unwrap_or(&execution.timestamp)
+}
+
+fn merge_test(existing: &mut TestReportTest, new_test: TestReportTest) {
+    for execution in new_test.executions {
+        if !existing.executions.contains(&execution) {
+            existing.executions.push(execution);
+        }
+    }
+
+    if let Some(new_tags) = new_test.tags {
+        let tags = existing.tags.get_or_insert_with(Vec::new);
+        for tag in new_tags {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+    }
+}
+
+fn sort_executions(executions: &mut [TestExecution]) {
+    executions.sort_by(|a, b| compare_timestamps(&a.timestamp, &b.timestamp));
+}
+
+fn is_older_than(timestamp: &str, oldest_allowed: DateTime<Utc>) -> bool {
+    parse_timestamp(timestamp).is_some_and(|timestamp| timestamp < oldest_allowed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+    use crate::test_identity::test_id;
+    use crate::test_report::{TestExecutionStatus, TestReportSuite};
+    use std::fs;
+    use std::io::Write;
+    use tempfile::{NamedTempFile, tempdir};
+
+    fn create_report(timestamp: &str, tests: Vec<TestReportTest>) -> TestReport {
+        TestReport {
+            version: REPORT_VERSION,
+            timestamp: timestamp.to_string(),
+            runs: Vec::new(),
+            suites: Vec::new(),
+            tests,
+        }
+    }
+
+    fn create_run(id: &str, timestamp: &str) -> TestRun {
+        TestRun {
+            id: id.to_string(),
+            timestamp: timestamp.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn create_execution_in_run(
+        run_id: &str,
+        timestamp: &str,
+        status: TestExecutionStatus,
+    ) -> TestExecution {
+        TestExecution {
+            run_id: Some(run_id.to_string()),
+            ..create_execution(timestamp, status)
+        }
+    }
+
+    fn create_test(
+        name: &str,
+        path: &str,
+        timestamp: &str,
+        status: TestExecutionStatus,
+        tags: Option<Vec<&str>>,
+    ) -> TestReportTest {
+        TestReportTest {
+            id: test_id(path, name),
+            name: name.to_string(),
+            path: path.to_string(),
+            executions: vec![create_execution(timestamp, status)],
+            tags: tags.map(|tags| tags.into_iter().map(|s| s.to_string()).collect()),
+            ..Default::default()
+        }
+    }
+
+    fn create_execution(timestamp: &str, status: TestExecutionStatus) -> TestExecution {
+        TestExecution {
+            timestamp: timestamp.to_string(),
+            ..test_fixtures::create_execution(status)
+        }
+    }
+
+    fn execution_timestamps(test: &TestReportTest) -> Vec<&str> {
+        test.executions
+            .iter()
+            .map(|execution| execution.timestamp.as_str())
+            .collect()
+    }
+
+    fn write_temp_report(report: &TestReport) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(serde_json::to_string(report).unwrap().as_bytes())
+            .unwrap();
+        file
+    }
+
+    #[test]
+    fn merging_without_history_returns_new_report() {
+        let new_report = create_report(
+            "2024-01-02T00:00:00Z",
+            vec![create_test(
+                "test1",
+                "suite",
+                "2024-01-02T00:00:00Z",
+                TestExecutionStatus::Passed,
+                None,
+            )],
+        );
+
+        let result = merge_reports(None, vec![new_report]);
+
+        assert_eq!(result.timestamp, "2024-01-02T00:00:00Z");
+        assert_eq!(result.tests.len(), 1);
+        assert_eq!(result.tests[0].executions.len(), 1);
+    }
+
+    #[test]
+    fn executions_of_the_same_test_are_appended_in_timestamp_order() {
+        let history = create_report(
+            "2024-01-02T00:00:00Z",
+            vec![create_test(
+                "test1",
+                "suite",
+                "2024-01-02T00:00:00Z",
+                TestExecutionStatus::Passed,
+                None,
+            )],
+        );
+        let newer_report = create_report(
+            "2024-01-03T00:00:00Z",
+            vec![create_test(
+                "test1",
+                "suite",
+                "2024-01-03T00:00:00Z",
+                TestExecutionStatus::Failed,
+                None,
+            )],
+        );
+        let older_report = create_report(
+            "2024-01-01T00:00:00Z",
+            vec![create_test(
+                "test1",
+                "suite",
+                "2024-01-01T00:00:00Z",
+                TestExecutionStatus::Passed,
+                None,
+            )],
+        );
+
+        let result = merge_reports(Some(history), vec![newer_report, older_report]);
+
+        assert_eq!(result.timestamp, "2024-01-03T00:00:00Z");
+        assert_eq!(result.tests.len(), 1);
+        assert_eq!(
+            execution_timestamps(&result.tests[0]),
+            vec![
+                "2024-01-01T00:00:00Z",
+                "2024-01-02T00:00:00Z",
+                "2024-01-03T00:00:00Z"
+            ]
+        );
+        assert_eq!(
+            result.tests[0].executions[2].status,
+            TestExecutionStatus::Failed
+        );
+    }
+
+    #[test]
+    fn tests_are_matched_by_id() {
+        let history = create_report(
+            "2024-01-01T00:00:00Z",
+            vec![create_test(
+                "test1",
+                "suite1",
+                "2024-01-01T00:00:00Z",
+                TestExecutionStatus::Passed,
+                None,
+            )],
+        );
+        let new_report = create_report(
+            "2024-01-02T00:00:00Z",
+            vec![create_test(
+                "test1",
+                "suite2",
+                "2024-01-02T00:00:00Z",
+                TestExecutionStatus::Passed,
+                None,
+            )],
+        );
+
+        let result = merge_reports(Some(history), vec![new_report]);
+
+        assert_eq!(result.tests.len(), 2);
+        assert_eq!(result.tests[0].path, "suite1");
+        assert_eq!(result.tests[1].path, "suite2");
+    }
+
+    #[test]
+    fn merging_the_same_report_twice_does_not_duplicate_executions() {
+        let report = create_report(
+            "2024-01-01T00:00:00Z",
+            vec![create_test(
+                "test1",
+                "suite",
+                "2024-01-01T00:00:00Z",
+                TestExecutionStatus::Passed,
+                None,
+            )],
+        );
+
+        let result = merge_reports(Some(report.clone()), vec![report]);
+
+        assert_eq!(result.tests[0].executions.len(), 1);
+    }
+
+    #[test]
+    fn tags_are_unioned() {
+        let history = create_report(
+            "2024-01-01T00:00:00Z",
+            vec![create_test(
+                "test1",
+                "suite",
+                "2024-01-01T00:00:00Z",
+                TestExecutionStatus::Passed,
+                Some(vec!["smoke", "owner:a"]),
+            )],
+        );
+        let new_report = create_report(
+            "2024-01-02T00:00:00Z",
+            vec![create_test(
+                "test1",
+                "suite",
+                "2024-01-02T00:00:00Z",
+                TestExecutionStatus::Passed,
+                Some(vec!["owner:a", "regression"]),
+            )],
+        );
+
+        let result = merge_reports(Some(history), vec![new_report]);
+
+        assert_eq!(
+            result.tests[0].tags,
+            Some(vec![
+                "smoke".to_string(),
+                "owner:a".to_string(),
+                "regression".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn retention_keeps_the_last_runs() {
+        let mut test = create_test(
+            "test1",
+            "suite",
+            "2024-01-01T00:00:00Z",
+            TestExecutionStatus::Passed,
+            None,
+        );
+        test.executions.push(create_execution(
+            "2024-01-02T00:00:00Z",
+            TestExecutionStatus::Failed,
+        ));
+        test.executions.push(create_execution(
+            "2024-01-03T00:00:00Z",
+            TestExecutionStatus::Passed,
+        ));
+        let mut report = create_report("2024-01-03T00:00:00Z", vec![test]);
+
+        apply_retention(
+            &mut report,
+            &Retention {
+                max_runs: Some(2),
+                max_age_days: None,
+            },
+        );
+
+        assert_eq!(
+            execution_timestamps(&report.tests[0]),
+            vec!["2024-01-02T00:00:00Z", "2024-01-03T00:00:00Z"]
+        );
+    }
+
+    #[test]
+    fn retention_removes_executions_older_than_max_age_and_empty_tests() {
+        let mut recent_test = create_test(
+            "test1",
+            "suite",
+            "2024-01-01T00:00:00Z",
+            TestExecutionStatus::Passed,
+            None,
+        );
+        recent_test.executions.push(create_execution(
+            "2024-01-09T00:00:00Z",
+            TestExecutionStatus::Passed,
+        ));
+        let removed_test = create_test(
+            "test2",
+            "suite",
+            "2024-01-01T00:00:00Z",
+            TestExecutionStatus::Passed,
+            None,
+        );
+        let mut report = create_report("2024-01-10T00:00:00Z", vec![recent_test, removed_test]);
+
+        apply_retention(
+            &mut report,
+            &Retention {
+                max_runs: None,
+                max_age_days: Some(7),
+            },
+        );
+
+        assert_eq!(report.tests.len(), 1);
+        assert_eq!(
+            execution_timestamps(&report.tests[0]),
+            vec!["2024-01-09T00:00:00Z"]
+        );
+    }
+
+    #[test]
+    fn runs_are_unioned_in_timestamp_order() {
+        let mut history = create_report("2024-01-02T00:00:00Z", vec![]);
+        history.runs = vec![create_run("run-2", "2024-01-02T00:00:00Z")];
+        let mut first_report = create_report("2024-01-01T00:00:00Z", vec![]);
+        first_report.runs = vec![create_run("run-1", "2024-01-01T00:00:00Z")];
+        let mut second_report = create_report("2024-01-02T00:00:00Z", vec![]);
+        second_report.runs = vec![create_run("run-2", "2024-01-02T00:00:00Z")];
+
+        let result = merge_reports(Some(history), vec![second_report, first_report]);
+
+        let run_ids: Vec<&str> = result.runs.iter().map(|run| run.id.as_str()).collect();
+        assert_eq!(run_ids, vec!["run-1", "run-2"]);
+    }
+
+    #[test]
+    fn retention_removes_executions_of_dropped_runs() {
+        let mut report = create_report(
+            "2024-01-03T00:00:00Z",
+            vec![
+                TestReportTest {
+                    executions: vec![
+                        create_execution_in_run(
+                            "run-1",
+                            "2024-01-01T00:00:00Z",
+                            TestExecutionStatus::Passed,
+                        ),
+                        create_execution_in_run(
+                            "run-1",
+                            "2024-01-01T00:00:01Z",
+                            TestExecutionStatus::Failed,
+                        ),
+                        create_execution_in_run(
+                            "run-3",
+                            "2024-01-03T00:00:00Z",
+                            TestExecutionStatus::Passed,
+                        ),
+                    ],
+                    ..create_test(
+                        "test1",
+                        "suite",
+                        "2024-01-01T00:00:00Z",
+                        TestExecutionStatus::Passed,
+                        None,
+                    )
+                },
+                TestReportTest {
+                    executions: vec![create_execution_in_run(
+                        "run-1",
+                        "2024-01-01T00:00:00Z",
+                        TestExecutionStatus::Passed,
+                    )],
+                    ..create_test(
+                        "test2",
+                        "suite",
+                        "2024-01-01T00:00:00Z",
+                        TestExecutionStatus::Passed,
+                        None,
+                    )
+                },
+            ],
+        );
+        report.runs = vec![
+            create_run("run-1", "2024-01-01T00:00:00Z"),
+            create_run("run-2", "2024-01-02T00:00:00Z"),
+            create_run("run-3", "2024-01-03T00:00:00Z"),
+        ];
+
+        apply_retention(
+            &mut report,
+            &Retention {
+                max_runs: Some(2),
+                max_age_days: None,
+            },
+        );
+
+        let run_ids: Vec<&str> = report.runs.iter().map(|run| run.id.as_str()).collect();
+        assert_eq!(run_ids, vec!["run-2", "run-3"]);
+        assert_eq!(report.tests.len(), 1);
+        assert_eq!(
+            execution_timestamps(&report.tests[0]),
+            vec!["2024-01-03T00:00:00Z"]
+        );
+    }
+
+    #[test]
+    fn retention_keeps_retries_of_the_same_run() {
+        let mut report = create_report(
+            "2024-01-02T00:00:00Z",
+            vec![TestReportTest {
+                executions: vec![
+                    create_execution_in_run(
+                        "run-1",
+                        "2024-01-01T00:00:00Z",
+                        TestExecutionStatus::Passed,
+                    ),
+                    create_execution_in_run(
+                        "run-2",
+                        "2024-01-02T00:00:00Z",
+                        TestExecutionStatus::Failed,
+                    ),
+                    create_execution_in_run(
+                        "run-2",
+                        "2024-01-02T00:00:01Z",
+                        TestExecutionStatus::Passed,
+                    ),
+                ],
+                ..create_test(
+                    "test1",
+                    "suite",
+                    "2024-01-01T00:00:00Z",
+                    TestExecutionStatus::Passed,
+                    None,
+                )
+            }],
+        );
+
+        apply_retention(
+            &mut report,
+            &Retention {
+                max_runs: Some(1),
+                max_age_days: None,
+            },
+        );
+
+        assert_eq!(
+            execution_timestamps(&report.tests[0]),
+            vec!["2024-01-02T00:00:00Z", "2024-01-02T00:00:01Z"]
+        );
+    }
+
+    #[test]
+    fn merge_command_upgrades_v1_history() {
+        let mut history_file = NamedTempFile::new().unwrap();
+        history_file
+            .write_all(
+                br#"{
+                    "version": 1,
+                    "timestamp": "2024-01-01T00:00:00Z",
+                    "tests": [
+                        {
+                            "name": "test1",
+                            "path": "suite",
+                            "executions": [
+                                { "timestamp": "2024-01-01T00:00:00Z", "status": "PASSED", "durationMs": 1 }
+                            ]
+                        }
+                    ]
+                }"#,
+            )
+            .unwrap();
+        let mut new_report = create_report(
+            "2024-01-02T00:00:00Z",
+            vec![TestReportTest {
+                executions: vec![create_execution_in_run(
+                    "run-2",
+                    "2024-01-02T00:00:00Z",
+                    TestExecutionStatus::Passed,
+                )],
+                ..create_test(
+                    "test1",
+                    "suite",
+                    "2024-01-02T00:00:00Z",
+                    TestExecutionStatus::Passed,
+                    None,
+                )
+            }],
+        );
+        new_report.runs = vec![create_run("run-2", "2024-01-02T00:00:00Z")];
+        let input_file = write_temp_report(&new_report);
+
+        merge_command(
+            history_file.path().to_str().unwrap().to_string(),
+            vec![input_file.path().to_str().unwrap().to_string()],
+            None,
+            Retention::default(),
+        );
+
+        let result_data = fs::read_to_string(history_file.path()).unwrap();
+        let result_report: TestReport = serde_json::from_str(&result_data).unwrap();
+        assert_eq!(result_report.version, REPORT_VERSION);
+        assert_eq!(result_report.runs.len(), 2);
+        assert_eq!(result_report.tests.len(), 1);
+        assert_eq!(
+            result_report.tests[0].executions[0].run_id,
+            Some("v1-2024-01-01T00:00:00Z".to_string())
+        );
+    }
+
+    #[test]
+    fn migrated_tests_are_matched_by_path_and_name() {
+        let mut history = create_report(
+            "2024-01-01T00:00:00Z",
+            vec![create_test(
+                "test1",
+                "suite",
+                "2024-01-01T00:00:00Z",
+                TestExecutionStatus::Passed,
+                None,
+            )],
+        );
+        history.tests[0].id = String::new();
+        history.tests[0].ensure_id();
+        let new_test = TestReportTest {
+            id: test_id("suite", "com.example.Suite#test1"),
+            ..create_test(
+                "test1",
+                "suite",
+                "2024-01-02T00:00:00Z",
+                TestExecutionStatus::Failed,
+                None,
+            )
+        };
+        let new_id = new_test.id.clone();
+
+        let merged = merge_reports(
+            Some(history),
+            vec![create_report("2024-01-02T00:00:00Z", vec![new_test])],
+        );
+
+        assert_eq!(merged.tests.len(), 1);
+        assert_eq!(merged.tests[0].id, new_id);
+        assert_eq!(
+            execution_timestamps(&merged.tests[0]),
+            vec!["2024-01-01T00:00:00Z", "2024-01-02T00:00:00Z"]
+        );
+    }
+
+    #[test]
+    fn merge_command_creates_history_when_missing() {
+        let dir = tempdir().unwrap();
+        let history_path = dir.path().join("history.json");
+        let input_file = write_temp_report(&create_report(
+            "2024-01-01T00:00:00Z",
+            vec![create_test(
+                "test1",
+                "suite",
+                "2024-01-01T00:00:00Z",
+                TestExecutionStatus::Passed,
+                None,
+            )],
+        ));
+
+        merge_command(
+            history_path.to_str().unwrap().to_string(),
+            vec![input_file.path().to_str().unwrap().to_string()],
+            None,
+            Retention::default(),
+        );
+
+        let result_data = fs::read_to_string(&history_path).unwrap();
+        let result_report: TestReport = serde_json::from_str(&result_data).unwrap();
+        assert_eq!(result_report.tests.len(), 1);
+        assert_eq!(result_report.timestamp, "2024-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn merge_command_writes_to_output_file() {
+        let history_file = write_temp_report(&create_report(
+            "2024-01-01T00:00:00Z",
+            vec![create_test(
+                "test1",
+                "suite",
+                "2024-01-01T00:00:00Z",
+                TestExecutionStatus::Passed,
+                None,
+            )],
+        ));
+        let input_file = write_temp_report(&create_report(
+            "2024-01-02T00:00:00Z",
+            vec![create_test(
+                "test1",
+                "suite",
+                "2024-01-02T00:00:00Z",
+                TestExecutionStatus::Failed,
+                None,
+            )],
+        ));
+        let output_file = NamedTempFile::new().unwrap();
+
+        merge_command(
+            history_file.path().to_str().unwrap().to_string(),
+            vec![input_file.path().to_str().unwrap().to_string()],
+            Some(output_file.path().to_str().unwrap().to_string()),
+            Retention::default(),
+        );
+
+        let result_data = fs::read_to_string(output_file.path()).unwrap();
+        let result_report: TestReport = serde_json::from_str(&result_data).unwrap();
+        assert_eq!(result_report.tests[0].executions.len(), 2);
+    }
+
+    fn create_suite(name: &str, run_id: &str, timestamp: &str) -> TestReportSuite {
+        TestReportSuite {
+            name: name.to_string(),
+            timestamp: timestamp.to_string(),
+            status: TestExecutionStatus::Error,
+            duration_ms: 10,
+            hostname: None,
+            declared_counts: None,
+            actual_counts: Default::default(),
+            errors: vec!["Exception in @BeforeAll".to_string()],
+            run_id: Some(run_id.to_string()),
+        }
+    }
+
+    #[test]
+    fn merge_keeps_suites_of_every_run_once() {
+        let mut history = create_report("2024-01-01T00:00:00Z", Vec::new());
+        history.suites = vec![create_suite("suite", "run-1", "2024-01-01T00:00:00Z")];
+        let mut new_report = create_report("2024-01-02T00:00:00Z", Vec::new());
+        new_report.suites = vec![
+            create_suite("suite", "run-1", "2024-01-01T00:00:00Z"),
+            create_suite("suite", "run-2", "2024-01-02T00:00:00Z"),
+        ];
+
+        let merged = merge_reports(Some(history), vec![new_report]);
+
+        let run_ids: Vec<_> = merged
+            .suites
+            .iter()
+            .map(|suite| suite.run_id.as_deref().unwrap())
+            .collect();
+        assert_eq!(run_ids, vec!["run-1", "run-2"]);
+    }
+
+    #[test]
+    fn retention_removes_suites_of_removed_runs() {
+        let mut report = create_report("2024-01-02T00:00:00Z", Vec::new());
+        report.runs = vec![
+            create_run("run-1", "2024-01-01T00:00:00Z"),
+            create_run("run-2", "2024-01-02T00:00:00Z"),
+        ];
+        report.suites = vec![
+            create_suite("suite", "run-1", "2024-01-01T00:00:00Z"),
+            create_suite("suite", "run-2", "2024-01-02T00:00:00Z"),
+        ];
+
+        apply_retention(
+            &mut report,
+            &Retention {
+                max_runs: Some(1),
+                max_age_days: None,
+            },
+        );
+
+        assert_eq!(report.suites.len(), 1);
+        assert_eq!(report.suites[0].run_id, Some("run-2".to_string()));
+    }
+}
@@ -1,2 +1,14 @@
-﻿pub mod parse_command;
+﻿pub mod clusters_command;
+pub mod diff_command;
+pub mod flaky_command;
+pub mod gate_command;
+pub mod list_formats_command;
+pub mod merge_command;
+pub mod parse_command;
+pub mod perf_command;
+pub mod query_command;
+pub mod schema_command;
+pub mod split_command;
+pub mod summary_command;
 pub mod tag_command;
+pub mod validate_command;
@@ -1,111 +1,355 @@
-﻿use crate::parsers::junit::JunitParser;
+﻿use crate::attachments::{AttachmentOptions, resolve_attachments};
+use crate::input_paths::{InputFilter, STDIO_PATH, resolve_input_paths};
+use crate::parser_registry::ParserRegistry;
+use crate::run_metadata::RunMetadata;
+use crate::tag_rules::{TagRule, apply_tag_rules};
+use crate::test_identity::test_id;
+use crate::test_models::{Test, TestSuite};
 use crate::test_parser::TestParser;
-use crate::test_report::{TestExecution, TestExecutionStatus, TestReport, TestReportTest};
-use std::path::Path;
-
-pub fn parse_command(
-    report_type: String,
-    input: String,
-    output: String,
-    current_date: String,
-    tags: Vec<String>,
-) {
-    let parser = get_parser(&report_type);
-    let input_paths = extract_folder_path_first_level(&input);
+use crate::test_report::{
+    REPORT_VERSION, TestExecution, TestExecutionStatus, TestReport, TestReportSuite,
+    TestReportTest, duration_ms_from_seconds, duration_us_from_seconds,
+};
+use crate::timestamps::{
+    SourceTimezone, format_timestamp, normalize_timestamp, parse_timestamp_in,
+};
+use chrono::Duration;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
-    let mut all_test_report_tests: Vec<TestReportTest> = Vec::new();
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    pub report_type: String,
+    pub inputs: Vec<String>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub output: String,
+    pub current_date: String,
+    pub tags: Vec<String>,
+    pub tag_rules: Vec<TagRule>,
+    pub jobs: usize,
+    pub run: RunMetadata,
+    pub attachments_dir: Option<String>,
+    pub inline_attachments_max_bytes: u64,
+    pub group_parameterized: bool,
+    pub timezone: SourceTimezone,
+    /// Whether to write an empty report when no input file is found.
+    pub allow_empty: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+struct TestStart {
+    time: Option<String>,
+    estimated: bool,
+}
+
+#[derive(Clone, Default)]
+struct ParsedFile {
+    suites: Vec<TestReportSuite>,
+    tests: Vec<TestReportTest>,
+}
 
-    for path_string in input_paths {
-        let path = Path::new(&path_string);
-        let tests = parse_file(&parser, &path_string, path, &tags, &current_date);
+/// Settings shared by the conversion of every parsed file.
+struct ConversionOptions<'a> {
+    tags: &'a [String],
+    timestamp: &'a str,
+    attachments: AttachmentOptions,
+    group_parameterized: bool,
+    timezone: SourceTimezone,
+}
+
+impl ConversionOptions<'_> {
+    /// Start of the suite as RFC 3339 UTC, or the report timestamp if the suite has none.
+    fn suite_timestamp(&self, suite: &TestSuite) -> String {
+        if suite.timestamp.trim().is_empty() {
+            return self.timestamp.to_string();
+        }
 
-        all_test_report_tests.extend(tests);
+        normalize_timestamp(&suite.timestamp, self.timezone).unwrap_or_else(|| {
+            eprintln!(
+                "Unsupported timestamp {} of suite {}",
+                suite.timestamp, suite.name
+            );
+            suite.timestamp.clone()
+        })
+    }
+}
+
+/// Parses the input files into a report. Returns false without writing the report if no input
+/// file was found, unless empty reports are allowed.
+pub fn parse_command(options: ParseOptions) -> bool {
+    parse_command_with_registry(&ParserRegistry::with_builtin_parsers(), options)
+}
+
+pub fn parse_command_with_registry(registry: &ParserRegistry, options: ParseOptions) -> bool {
+    let parser = registry
+        .resolve(&options.report_type)
+        .unwrap_or_else(|e| panic!("{}", e));
+    let input_filter = InputFilter::new(
+        &options.include,
+        &options.exclude,
+        &registry.file_extensions(&options.report_type),
+    )
+    .unwrap_or_else(|e| panic!("{}", e));
+    let input_paths = resolve_input_paths(&options.inputs, &input_filter);
+    if input_paths.is_empty() && !options.allow_empty {
+        eprintln!("No input files found");
+        return false;
+    }
+    let report_dir = report_dir(&options.output);
+    let conversion = ConversionOptions {
+        tags: &options.tags,
+        timestamp: &options.current_date,
+        attachments: AttachmentOptions {
+            bundle_dir: options
+                .attachments_dir
+                .as_ref()
+                .map(|attachments_dir| report_dir.join(attachments_dir)),
+            report_dir,
+            inline_max_bytes: options.inline_attachments_max_bytes,
+        },
+        group_parameterized: options.group_parameterized,
+        timezone: options.timezone,
+    };
+
+    let parsed_files = parse_files(
+        parser.as_ref(),
+        &input_paths,
+        &conversion,
+        effective_jobs(options.jobs),
+    );
+
+    let mut all_test_report_suites: Vec<TestReportSuite> = Vec::new();
+    let mut all_test_report_tests: Vec<TestReportTest> = Vec::new();
+    for parsed_file in parsed_files {
+        all_test_report_suites.extend(parsed_file.suites);
+        all_test_report_tests.extend(parsed_file.tests);
+    }
+    apply_tag_rules(&mut all_test_report_tests, &options.tag_rules);
+
+    let run = options
+        .run
+        .to_test_run(&options.current_date, &options.report_type);
+    for suite in &mut all_test_report_suites {
+        suite.run_id = Some(run.id.clone());
+    }
+    for execution in all_test_report_tests
+        .iter_mut()
+        .flat_map(|test| test.executions.iter_mut())
+    {
+        execution.run_id = Some(run.id.clone());
     }
 
     let test_report = TestReport {
-        version: 1,
-        timestamp: current_date.to_string(),
+        version: REPORT_VERSION,
+        timestamp: options.current_date.to_string(),
+        runs: vec![run],
+        suites: all_test_report_suites,
         tests: all_test_report_tests,
     };
 
-    write_test_report(&test_report, &output);
+    write_test_report(&test_report, &options.output);
+    true
 }
 
-fn parse_file(
-    parser: &impl TestParser,
-    path_str: &str,
-    path: &Path,
-    tags: &[String],
-    timestamp: &str,
-) -> Vec<TestReportTest> {
-    match parser.parse(path) {
-        Ok(tests) => tests
+fn report_dir(output_path: &str) -> PathBuf {
+    match Path::new(output_path).parent() {
+        Some(parent) if output_path != STDIO_PATH && !parent.as_os_str().is_empty() => {
+            parent.to_path_buf()
+        }
+        _ => PathBuf::from("."),
+    }
+}
+
+fn effective_jobs(jobs: usize) -> usize {
+    if jobs > 0 {
+        return jobs;
+    }
+
+    thread::available_parallelism()
+        .map(|parallelism| parallelism.get())
+        .unwrap_or(1)
+}
+
+fn parse_files(
+    parser: &dyn TestParser,
+    input_paths: &[String],
+    conversion: &ConversionOptions,
+    jobs: usize,
+) -> Vec<ParsedFile> {
+    let worker_count = jobs.min(input_paths.len());
+    if worker_count <= 1 {
+        return input_paths
             .iter()
-            .flat_map(|suite| {
-                suite.tests.iter().map(move |test| {
-                    let execution = TestExecution {
-                        timestamp: if suite.timestamp.is_empty() {
-                            timestamp.to_string()
-                        } else {
-                            suite.timestamp.clone()
-                        },
-                        status: TestExecutionStatus::from_test_status(&test.status),
-                        duration_ms: (test.time * 1000.0) as u64,
-                        message: TestExecution::message_from_test_status(&test.status),
-                    };
-
-                    TestReportTest {
-                        name: test.name.clone(),
-                        path: suite.name.clone(),
-                        executions: vec![execution],
-                        tags: if tags.is_empty() {
-                            None
-                        } else {
-                            Some(tags.to_vec())
-                        },
+            .map(|path_string| parse_file(parser, path_string, conversion))
+            .collect();
+    }
+
+    let next_index = AtomicUsize::new(0);
+    let mut results_by_file: Vec<ParsedFile> = vec![ParsedFile::default(); input_paths.len()];
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..worker_count)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut parsed_files = Vec::new();
+                    loop {
+                        let index = next_index.fetch_add(1, Ordering::Relaxed);
+                        if index >= input_paths.len() {
+                            break;
+                        }
+                        let parsed_file = parse_file(parser, &input_paths[index], conversion);
+                        parsed_files.push((index, parsed_file));
                     }
+                    parsed_files
                 })
             })
-            .collect(),
+            .collect();
+
+        for worker in workers {
+            for (index, parsed_file) in worker.join().expect("Parser thread panicked") {
+                results_by_file[index] = parsed_file;
+            }
+        }
+    });
+
+    results_by_file
+}
+
+fn parse_file(
+    parser: &dyn TestParser,
+    path_str: &str,
+    conversion: &ConversionOptions,
+) -> ParsedFile {
+    let (result, source_dir) = if path_str == STDIO_PATH {
+        (parser.parse_reader(&mut std::io::stdin().lock()), None)
+    } else {
+        let path = Path::new(path_str);
+        (parser.parse(path), path.parent())
+    };
+
+    match result {
+        Ok(suites) => {
+            let mut parsed_file = ParsedFile::default();
+            for suite in &suites {
+                let suite_timestamp = conversion.suite_timestamp(suite);
+                let test_starts = test_starts(suite, conversion.timezone);
+                parsed_file
+                    .tests
+                    .extend(suite.tests.iter().zip(test_starts).map(|(test, start)| {
+                        convert_to_test_report_test(
+                            suite,
+                            test,
+                            &suite_timestamp,
+                            start,
+                            source_dir,
+                            conversion,
+                        )
+                    }));
+                parsed_file
+                    .suites
+                    .push(TestReportSuite::from_test_suite(suite, suite_timestamp));
+            }
+            parsed_file
+        }
         Err(e) => {
             eprintln!("Error parsing file {}: {}", path_str, e);
-            vec![]
+            ParsedFile::default()
         }
     }
 }
 
-fn get_parser(report_type: &str) -> impl TestParser {
-    match report_type {
-        "junit" => JunitParser,
-        _ => panic!(
-            "Unknown report_type: {}. Supported types: junit",
-            report_type
-        ),
-    }
+/// Start times of the tests of a suite. Tests without a start time in the report are assumed
+/// to run one after the other from the start of the suite, or from the end of the previous
+/// test with a known start time.
+fn test_starts(suite: &TestSuite, timezone: SourceTimezone) -> Vec<TestStart> {
+    let mut next_start = parse_timestamp_in(&suite.timestamp, timezone);
+
+    suite
+        .tests
+        .iter()
+        .map(|test| {
+            let reported_start = test
+                .start_time
+                .as_deref()
+                .and_then(|start_time| parse_timestamp_in(start_time, timezone));
+            let estimated = reported_start.is_none() && next_start.is_some();
+            let start = reported_start.or(next_start);
+
+            let duration = duration_us_from_seconds(test.time).unwrap_or(0);
+            next_start = start.map(|start| start + Duration::microseconds(duration as i64));
+            TestStart {
+                time: start.map(format_timestamp),
+                estimated,
+            }
+        })
+        .collect()
 }
 
-fn extract_folder_path_first_level(file_path: &str) -> Vec<String> {
-    let path = Path::new(file_path);
-
-    if path.is_dir() {
-        path.read_dir()
-            .unwrap()
-            .filter_map(|entry| {
-                let entry = entry.unwrap();
-                let entry_path = entry.path();
-                if entry_path.is_file() {
-                    Some(entry_path.to_str().unwrap().to_string())
-                } else {
-                    None
-                }
-            })
-            .collect()
-    } else {
-        vec![file_path.to_string()]
+fn convert_to_test_report_test(
+    suite: &TestSuite,
+    test: &Test,
+    suite_timestamp: &str,
+    start: TestStart,
+    source_dir: Option<&Path>,
+    conversion: &ConversionOptions,
+) -> TestReportTest {
+    let id = test_id(&suite.name, test.identity.as_deref().unwrap_or(&test.name));
+    let duration_us = duration_us_from_seconds(test.time);
+    if duration_us.is_none() {
+        eprintln!(
+            "Invalid duration {} of test {} in suite {}",
+            test.time, test.name, suite.name
+        );
+    }
+    let execution = TestExecution {
+        timestamp: suite_timestamp.to_string(),
+        status: TestExecutionStatus::from_test_status(&test.status),
+        duration_ms: duration_ms_from_seconds(test.time).unwrap_or(0),
+        duration_us: Some(duration_us.unwrap_or(0)),
+        invalid_duration: duration_us.is_none(),
+        message: TestExecution::message_from_test_status(&test.status),
+        run_id: None,
+        attachments: resolve_attachments(
+            &test.attachments,
+            source_dir,
+            &id,
+            &conversion.attachments,
+        ),
+        start_time: start.time,
+        start_time_estimated: start.estimated,
+        stack_trace: test.stack_trace.clone(),
+        cluster_id: None,
+    };
+
+    TestReportTest {
+        id,
+        name: test.name.clone(),
+        path: suite.name.clone(),
+        executions: vec![execution],
+        tags: if conversion.tags.is_empty() {
+            None
+        } else {
+            Some(conversion.tags.to_vec())
+        },
+        parameters: test.parameters.clone(),
+        parent: test
+            .parent
+            .clone()
+            .filter(|_| conversion.group_parameterized),
     }
 }
 
 fn write_test_report(test_report: &TestReport, output_path: &str) {
+    if output_path == STDIO_PATH {
+        let mut writer = std::io::stdout().lock();
+        serde_json::to_writer(&mut writer, test_report).expect("Failed to write to stdout");
+        writeln!(writer).expect("Failed to write to stdout");
+        return;
+    }
+
     let output_path = Path::new(output_path);
     let file = std::fs::File::create(output_path).expect("Failed to create output file");
     let writer = std::io::BufWriter::new(file);
@@ -116,75 +360,424 @@ fn write_test_report(test_report: &TestReport, output_path: &str) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tag_rules::TagOperation;
     use std::fs;
-    use std::fs::File;
     use std::io::Write;
-    use tempfile::{tempdir, NamedTempFile};
+    use tempfile::{NamedTempFile, tempdir};
 
     #[test]
     fn panic_if_unsupported_report_type() {
         let report_type = "unsupported";
 
-        let result = std::panic::catch_unwind(|| get_parser(report_type));
+        let result = std::panic::catch_unwind(|| {
+            parse_command(ParseOptions {
+                report_type: report_type.to_string(),
+                ..Default::default()
+            })
+        });
 
         assert!(result.is_err());
     }
 
     #[test]
-    fn given_file_path_when_path_is_file_return_list_with_one_element() {
-        let file = NamedTempFile::new().expect("Failed to create temp file");
-        let file_path = file.path().to_str().unwrap().to_string();
+    fn when_no_input_file_is_found_it_should_fail_unless_empty_reports_are_allowed() {
+        let dir = tempdir().unwrap();
+        let output = dir.path().join("report.json");
+        let options = ParseOptions {
+            report_type: "junit".to_string(),
+            inputs: vec![format!("{}/nomatch/**/*.xml", dir.path().to_str().unwrap())],
+            output: output.to_str().unwrap().to_string(),
+            current_date: "2024-01-01T00:00:00Z".to_string(),
+            ..Default::default()
+        };
 
-        let result = extract_folder_path_first_level(&file_path);
+        assert!(!parse_command(options.clone()));
+        assert!(!output.exists());
 
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0], file_path);
+        assert!(parse_command(ParseOptions {
+            allow_empty: true,
+            ..options
+        }));
+        let result_report: TestReport =
+            serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert!(result_report.tests.is_empty());
     }
 
     #[test]
-    fn given_file_path_when_path_is_folder_return_list_with_the_file_paths_from_the_folder() {
-        let dir = tempdir().expect("Failed to create temp dir");
-        let file1_path = dir.path().join("file1.xml");
-        let file2_path = dir.path().join("file2.xml");
-        File::create(&file1_path).expect("Failed to create file1");
-        File::create(&file2_path).expect("Failed to create file2");
+    fn when_parsing_a_file_with_tags_it_should_add_the_tags_to_all_tests() {
+        let mut input_file = NamedTempFile::new().unwrap();
+        let output_file = NamedTempFile::new().unwrap();
+        let xml_content = r#"
+            <testsuite name="suite1" tests="1" failures="0" errors="0" skipped="0" timestamp="2024-01-01T00:00:00Z" time="1.0">
+                <testcase name="test1" classname="class1" time="1.0" />
+            </testsuite>
+        "#;
+        input_file.write_all(xml_content.as_bytes()).unwrap();
+
+        parse_command(ParseOptions {
+            report_type: "junit".to_string(),
+            inputs: vec![input_file.path().to_str().unwrap().to_string()],
+            output: output_file.path().to_str().unwrap().to_string(),
+            current_date: "2024-01-01T00:00:00Z".to_string(),
+            tags: vec!["tag1".to_string(), "tag2".to_string()],
+            ..Default::default()
+        });
 
-        let folder_path = dir.path().to_str().unwrap();
-        let result = extract_folder_path_first_level(folder_path);
+        let result_data = fs::read_to_string(output_file.path()).unwrap();
+        let result_report: TestReport = serde_json::from_str(&result_data).unwrap();
 
-        assert_eq!(result.len(), 2);
-        assert!(result.contains(&file1_path.to_str().unwrap().to_string()));
-        assert!(result.contains(&file2_path.to_str().unwrap().to_string()));
+        assert_eq!(
+            result_report.tests[0].tags,
+            Some(vec!["tag1".to_string(), "tag2".to_string()])
+        );
     }
 
     #[test]
-    fn when_parsing_a_file_with_tags_it_should_add_the_tags_to_all_tests() {
+    fn when_parsing_a_file_with_tag_rules_it_should_apply_them_after_the_tags() {
         let mut input_file = NamedTempFile::new().unwrap();
         let output_file = NamedTempFile::new().unwrap();
+        let xml_content = r#"
+            <testsuite name="suite1" tests="2" failures="1" errors="0" skipped="0" timestamp="2024-01-01T00:00:00Z" time="7.0">
+                <testcase name="test1" classname="class1" time="1.0" />
+                <testcase name="test2" classname="class1" time="6.0">
+                    <failure message="Timeout: no response">timeout</failure>
+                </testcase>
+            </testsuite>
+        "#;
+        input_file.write_all(xml_content.as_bytes()).unwrap();
+
+        parse_command(ParseOptions {
+            report_type: "junit".to_string(),
+            inputs: vec![input_file.path().to_str().unwrap().to_string()],
+            output: output_file.path().to_str().unwrap().to_string(),
+            current_date: "2024-01-01T00:00:00Z".to_string(),
+            tags: vec!["nightly".to_string()],
+            tag_rules: vec![
+                TagRule::new(
+                    "message ~ 'Timeout:' and duration > 5s",
+                    TagOperation::Add,
+                    vec!["timeout".to_string()],
+                )
+                .unwrap(),
+                TagRule::new(
+                    "tag == timeout",
+                    TagOperation::Remove,
+                    vec!["nightly".to_string()],
+                )
+                .unwrap(),
+            ],
+            ..Default::default()
+        });
+
+        let result_data = fs::read_to_string(output_file.path()).unwrap();
+        let result_report: TestReport = serde_json::from_str(&result_data).unwrap();
+
+        assert_eq!(
+            result_report.tests[0].tags,
+            Some(vec!["nightly".to_string()])
+        );
+        assert_eq!(
+            result_report.tests[1].tags,
+            Some(vec!["timeout".to_string()])
+        );
+    }
+
+    #[test]
+    fn when_parsing_a_folder_it_should_only_parse_files_with_known_extensions() {
+        let dir = tempdir().unwrap();
+        let output_file = NamedTempFile::new().unwrap();
         let xml_content = r#"
             <testsuite name="suite1" tests="1" failures="0" errors="0" skipped="0" timestamp="2024-01-01T00:00:00Z" time="1.0">
                 <testcase name="test1" classname="class1" time="1.0" />
             </testsuite>
         "#;
+        fs::create_dir_all(dir.path().join("module")).unwrap();
+        fs::write(
+            dir.path().join("module").join("TEST-suite1.xml"),
+            xml_content,
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a report").unwrap();
+
+        parse_command(ParseOptions {
+            report_type: "junit".to_string(),
+            inputs: vec![dir.path().to_str().unwrap().to_string()],
+            output: output_file.path().to_str().unwrap().to_string(),
+            current_date: "2024-01-01T00:00:00Z".to_string(),
+            ..Default::default()
+        });
+
+        let result_data = fs::read_to_string(output_file.path()).unwrap();
+        let result_report: TestReport = serde_json::from_str(&result_data).unwrap();
+
+        assert_eq!(result_report.tests.len(), 1);
+        assert_eq!(result_report.tests[0].name, "test1");
+    }
+
+    #[test]
+    fn when_parsing_files_in_parallel_it_should_keep_the_order_of_the_files() {
+        let dir = tempdir().unwrap();
+        for index in 0..20 {
+            let xml_content = format!(
+                r#"
+                <testsuite name="suite{index}" tests="2" failures="0" errors="0" skipped="0" timestamp="2024-01-01T00:00:00Z" time="1.0">
+                    <testcase name="test{index}_a" classname="class{index}" time="0.5" />
+                    <testcase name="test{index}_b" classname="class{index}" time="0.5" />
+                </testsuite>
+            "#
+            );
+            fs::write(dir.path().join(format!("TEST-{index:02}.xml")), xml_content).unwrap();
+        }
+        let sequential_output = NamedTempFile::new().unwrap();
+        let parallel_output = NamedTempFile::new().unwrap();
+        let options = ParseOptions {
+            report_type: "junit".to_string(),
+            inputs: vec![dir.path().to_str().unwrap().to_string()],
+            current_date: "2024-01-01T00:00:00Z".to_string(),
+            ..Default::default()
+        };
+
+        parse_command(ParseOptions {
+            output: sequential_output.path().to_str().unwrap().to_string(),
+            jobs: 1,
+            ..options.clone()
+        });
+        parse_command(ParseOptions {
+            output: parallel_output.path().to_str().unwrap().to_string(),
+            jobs: 4,
+            ..options
+        });
+
+        let sequential_data = fs::read_to_string(sequential_output.path()).unwrap();
+        let parallel_data = fs::read_to_string(parallel_output.path()).unwrap();
+        let parallel_report: TestReport = serde_json::from_str(&parallel_data).unwrap();
+
+        assert_eq!(parallel_data, sequential_data);
+        assert_eq!(parallel_report.tests.len(), 40);
+        assert_eq!(parallel_report.tests[0].name, "test0_a");
+        assert_eq!(parallel_report.tests[39].name, "test19_b");
+    }
+
+    #[test]
+    fn when_parsing_a_file_with_attachments_it_should_copy_them_next_to_the_output() {
+        let input_dir = tempdir().unwrap();
+        let output_dir = tempdir().unwrap();
+        let xml_content = r#"
+            <testsuite name="suite1" tests="1" failures="1" timestamp="2024-01-01T00:00:00Z" time="1.0">
+                <testcase name="test1" classname="class1" time="1.0">
+                    <failure message="boom"/>
+                    <system-out>[[ATTACHMENT|screenshots/test1.png]]</system-out>
+                </testcase>
+            </testsuite>
+        "#;
+        fs::create_dir_all(input_dir.path().join("screenshots")).unwrap();
+        fs::write(input_dir.path().join("screenshots/test1.png"), [0u8; 32]).unwrap();
+        let input_path = input_dir.path().join("TEST-suite1.xml");
+        fs::write(&input_path, xml_content).unwrap();
+        let output_path = output_dir.path().join("report.json");
+
+        parse_command(ParseOptions {
+            report_type: "junit".to_string(),
+            inputs: vec![input_path.to_str().unwrap().to_string()],
+            output: output_path.to_str().unwrap().to_string(),
+            current_date: "2024-01-01T00:00:00Z".to_string(),
+            attachments_dir: Some("attachments".to_string()),
+            ..Default::default()
+        });
+
+        let result_data = fs::read_to_string(&output_path).unwrap();
+        let result_report: TestReport = serde_json::from_str(&result_data).unwrap();
+        let test = &result_report.tests[0];
+        let attachment = &test.executions[0].attachments[0];
+        let expected_path = format!("attachments/{}/test1.png", test.id);
+        assert_eq!(attachment.name, "test1.png");
+        assert_eq!(attachment.mime_type, "image/png");
+        assert_eq!(attachment.path.as_deref(), Some(expected_path.as_str()));
+        assert!(output_dir.path().join(expected_path).exists());
+    }
+
+    #[test]
+    fn when_grouping_parameterized_tests_it_should_set_the_parent_of_invocations() {
+        let mut input_file = NamedTempFile::new().unwrap();
+        let output_file = NamedTempFile::new().unwrap();
+        let xml_content = r#"
+            <testsuite name="suite1" tests="2" timestamp="2024-01-01T00:00:00Z" time="1.0">
+                <testcase name="testAdd[0: 1, 2]" classname="class1" time="0.5" />
+                <testcase name="testAdd[1: 3, 4]" classname="class1" time="0.5" />
+            </testsuite>
+        "#;
         input_file.write_all(xml_content.as_bytes()).unwrap();
 
-        parse_command(
-            "junit".to_string(),
-            input_file.path().to_str().unwrap().to_string(),
-            output_file.path().to_str().unwrap().to_string(),
-            "2024-01-01T00:00:00Z".to_string(),
-            vec!["tag1".to_string(), "tag2".to_string()],
+        parse_command(ParseOptions {
+            report_type: "junit".to_string(),
+            inputs: vec![input_file.path().to_str().unwrap().to_string()],
+            output: output_file.path().to_str().unwrap().to_string(),
+            current_date: "2024-01-01T00:00:00Z".to_string(),
+            group_parameterized: true,
+            ..Default::default()
+        });
+
+        let result_data = fs::read_to_string(output_file.path()).unwrap();
+        let result_report: TestReport = serde_json::from_str(&result_data).unwrap();
+        assert_eq!(
+            result_report.tests[0].parent.as_deref(),
+            Some("class1#testAdd")
         );
+        assert_eq!(
+            result_report.tests[1].parent.as_deref(),
+            Some("class1#testAdd")
+        );
+        assert_eq!(result_report.tests[1].parameters["0"], "3");
+        assert_eq!(result_report.tests[1].parameters["1"], "4");
+    }
+
+    #[test]
+    fn when_parsing_timestamps_without_zone_it_should_use_the_source_timezone() {
+        let mut input_file = NamedTempFile::new().unwrap();
+        let output_file = NamedTempFile::new().unwrap();
+        let xml_content = r#"
+            <testsuite name="suite1" tests="1" timestamp="2024-07-01T10:00:00" time="1.0">
+                <testcase name="test1" classname="class1" time="1.0" />
+            </testsuite>
+        "#;
+        input_file.write_all(xml_content.as_bytes()).unwrap();
+
+        parse_command(ParseOptions {
+            report_type: "junit".to_string(),
+            inputs: vec![input_file.path().to_str().unwrap().to_string()],
+            output: output_file.path().to_str().unwrap().to_string(),
+            current_date: "2024-07-02T00:00:00Z".to_string(),
+            timezone: "Europe/Berlin".parse().unwrap(),
+            ..Default::default()
+        });
+
+        let result_data = fs::read_to_string(output_file.path()).unwrap();
+        let result_report: TestReport = serde_json::from_str(&result_data).unwrap();
+        assert_eq!(
+            result_report.tests[0].executions[0].timestamp,
+            "2024-07-01T08:00:00Z"
+        );
+        assert_eq!(result_report.suites[0].timestamp, "2024-07-01T08:00:00Z");
+    }
+
+    #[test]
+    fn when_parsing_durations_it_should_keep_microseconds_and_flag_invalid_values() {
+        let mut input_file = NamedTempFile::new().unwrap();
+        let output_file = NamedTempFile::new().unwrap();
+        let xml_content = r#"
+            <testsuite name="suite1" tests="4" timestamp="2024-01-01T00:00:00Z" time="0.0026">
+                <testcase name="fast" classname="class1" time="0.000042" />
+                <testcase name="rounded" classname="class1" time="0.0026" />
+                <testcase name="negative" classname="class1" time="-1.5" />
+                <testcase name="nan" classname="class1" time="NaN" />
+            </testsuite>
+        "#;
+        input_file.write_all(xml_content.as_bytes()).unwrap();
+
+        parse_command(ParseOptions {
+            report_type: "junit".to_string(),
+            inputs: vec![input_file.path().to_str().unwrap().to_string()],
+            output: output_file.path().to_str().unwrap().to_string(),
+            current_date: "2024-01-01T00:00:00Z".to_string(),
+            ..Default::default()
+        });
 
         let result_data = fs::read_to_string(output_file.path()).unwrap();
         let result_report: TestReport = serde_json::from_str(&result_data).unwrap();
+        let durations: Vec<(u64, Option<u64>, bool)> = result_report
+            .tests
+            .iter()
+            .map(|test| {
+                let execution = &test.executions[0];
+                (
+                    execution.duration_ms,
+                    execution.duration_us,
+                    execution.invalid_duration,
+                )
+            })
+            .collect();
+        assert_eq!(
+            durations,
+            vec![
+                (0, Some(42), false),
+                (3, Some(2600), false),
+                (0, Some(0), true),
+                (0, Some(0), true),
+            ]
+        );
+        assert_eq!(result_report.suites[0].duration_ms, 3);
+    }
+
+    fn create_test(name: &str, time: f64, start_time: Option<&str>) -> Test {
+        Test {
+            name: name.to_string(),
+            identity: None,
+            time,
+            status: crate::test_models::TestStatus::Passed,
+            attachments: Vec::new(),
+            parameters: Default::default(),
+            parent: None,
+            start_time: start_time.map(|start_time| start_time.to_string()),
+            stack_trace: None,
+        }
+    }
+
+    #[test]
+    fn test_starts_are_estimated_from_suite_start_and_durations() {
+        let suite = TestSuite {
+            timestamp: "2024-01-01T10:00:00Z".to_string(),
+            tests: vec![
+                create_test("a", 0.25, None),
+                create_test("b", 1.0, None),
+                create_test("c", 0.5, Some("2024-01-01T10:00:05Z")),
+                create_test("d", 0.0, None),
+            ],
+            ..Default::default()
+        };
+
+        let starts = test_starts(&suite, SourceTimezone::Utc);
 
         assert_eq!(
-            result_report.tests[0].tags,
-            Some(vec!["tag1".to_string(), "tag2".to_string()])
+            starts,
+            vec![
+                TestStart {
+                    time: Some("2024-01-01T10:00:00Z".to_string()),
+                    estimated: true,
+                },
+                TestStart {
+                    time: Some("2024-01-01T10:00:00.250Z".to_string()),
+                    estimated: true,
+                },
+                TestStart {
+                    time: Some("2024-01-01T10:00:05Z".to_string()),
+                    estimated: false,
+                },
+                TestStart {
+                    time: Some("2024-01-01T10:00:05.500Z".to_string()),
+                    estimated: true,
+                },
+            ]
         );
     }
 
+    #[test]
+    fn test_starts_are_unknown_without_suite_start() {
+        let suite = TestSuite {
+            tests: vec![create_test("a", 0.25, None)],
+            ..Default::default()
+        };
+
+        let starts = test_starts(&suite, SourceTimezone::Utc);
+
+        assert_eq!(starts, vec![TestStart::default()]);
+    }
+
+    #[test]
+    fn effective_jobs_uses_available_parallelism_when_not_set() {
+        assert!(effective_jobs(0) >= 1);
+        assert_eq!(effective_jobs(3), 3);
+    }
+
     #[test]
     fn when_parsing_a_file_without_tags_it_should_not_add_any_tags() {
         let mut input_file = NamedTempFile::new().unwrap();
@@ -196,13 +789,13 @@ mod tests {
         "#;
         input_file.write_all(xml_content.as_bytes()).unwrap();
 
-        parse_command(
-            "junit".to_string(),
-            input_file.path().to_str().unwrap().to_string(),
-            output_file.path().to_str().unwrap().to_string(),
-            "2024-01-01T00:00:00Z".to_string(),
-            vec![],
-        );
+        parse_command(ParseOptions {
+            report_type: "junit".to_string(),
+            inputs: vec![input_file.path().to_str().unwrap().to_string()],
+            output: output_file.path().to_str().unwrap().to_string(),
+            current_date: "2024-01-01T00:00:00Z".to_string(),
+            ..Default::default()
+        });
 
         let result_data = fs::read_to_string(output_file.path()).unwrap();
         let result_report: TestReport = serde_json::from_str(&result_data).unwrap();
@@ -0,0 +1,346 @@
+use crate::output_format::{OutputFormat, escape_markdown_cell, test_label};
+use crate::report_io::{read_report, write_output};
+use crate::report_stats::{median, median_absolute_deviation};
+use crate::test_report::{TestExecution, TestExecutionStatus, TestReport, TestReportTest};
+use crate::timestamps::compare_timestamps;
+use serde::Serialize;
+
+/// Scales the median absolute deviation to the standard deviation of normally distributed
+/// durations.
+const MAD_SCALE: f64 = 1.4826;
+
+#[derive(Debug, Clone)]
+pub struct PerfOptions {
+    pub input: String,
+    pub output: Option<String>,
+    pub format: OutputFormat,
+    pub top: usize,
+    /// Number of previous normal executions the baseline is computed from.
+    pub window: usize,
+    /// Minimum number of normal executions before executions are compared to the baseline.
+    pub min_baseline: usize,
+    /// Number of most recent executions that must all be slow for a regression.
+    pub recent: usize,
+    /// Minimum distance from the baseline median, in scaled median absolute deviations.
+    pub threshold: f64,
+    pub min_increase_ms: u64,
+    pub min_increase_percent: f64,
+}
+
+impl Default for PerfOptions {
+    fn default() -> Self {
+        PerfOptions {
+            input: String::new(),
+            output: None,
+            format: OutputFormat::Text,
+            top: 10,
+            window: 10,
+            min_baseline: 5,
+            recent: 3,
+            threshold: 3.5,
+            min_increase_ms: 100,
+            min_increase_percent: 20.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DurationRegression {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    /// Median duration of the executions before the regression.
+    #[serde(rename = "baselineMs")]
+    pub baseline_ms: f64,
+    /// Median duration of the executions since the regression started.
+    #[serde(rename = "currentMs")]
+    pub current_ms: f64,
+    #[serde(rename = "increasePercent")]
+    pub increase_percent: f64,
+    /// Number of slow executions since the regression started.
+    pub executions: usize,
+    /// Timestamp of the first slow execution.
+    #[serde(rename = "startedAt")]
+    pub started_at: String,
+    /// Run of the first slow execution.
+    #[serde(rename = "startRunId", skip_serializing_if = "Option::is_none")]
+    pub start_run_id: Option<String>,
+}
+
+pub fn perf_command(options: PerfOptions) {
+    let report = read_report(&options.input).expect("Failed to read input file");
+
+    let mut regressions = find_regressions(&report, &options);
+    regressions.truncate(options.top);
+    let content = match options.format {
+        OutputFormat::Text => format_text(&regressions),
+        OutputFormat::Json => format!(
+            "{}\n",
+            serde_json::to_string_pretty(&regressions)
+                .expect("Failed to serialize the regressions")
+        ),
+        OutputFormat::Markdown => format_markdown(&regressions),
+    };
+    write_output(&content, options.output.as_deref()).expect("Failed to write the regressions");
+}
+
+/// Finds the tests whose most recent passed executions are all significantly slower than the
+/// executions before them, worst regression first.
+pub fn find_regressions(report: &TestReport, options: &PerfOptions) -> Vec<DurationRegression> {
+    let mut regressions: Vec<DurationRegression> = report
+        .tests
+        .iter()
+        .filter_map(|test| find_regression(test, options))
+        .collect();
+    regressions.sort_by(|a, b| b.increase_percent.total_cmp(&a.increase_percent));
+    regressions
+}
+
+/// Compares every passed execution to the median of the previous normal executions. Slow
+/// executions are left out of the baseline, so a lasting slowdown keeps being detected. A
+/// gradual slowdown, which the rolling baseline follows, is found by `find_drift` instead.
+fn find_regression(test: &TestReportTest, options: &PerfOptions) -> Option<DurationRegression> {
+    let mut executions: Vec<&TestExecution> = test
+        .executions
+        .iter()
+        .filter(|execution| execution.status == TestExecutionStatus::Passed)
+        .collect();
+    executions.sort_by(|a, b| compare_timestamps(&a.timestamp, &b.timestamp));
+
+    let mut normal: Vec<u64> = Vec::new();
+    let mut baseline_ms = 0.0;
+    let mut slow_since: Option<usize> = None;
+    for (index, execution) in executions.iter().enumerate() {
+        let baseline = &normal[normal.len().saturating_sub(options.window)..];
+        if baseline.len() >= options.min_baseline
+            && is_slow(execution.duration_ms, baseline, options)
+        {
+            if slow_since.is_none() {
+                baseline_ms = median(baseline);
+            }
+            slow_since.get_or_insert(index);
+        } else {
+            normal.push(execution.duration_ms);
+            slow_since = None;
+        }
+    }
+
+    let (baseline_ms, start) = match slow_since {
+        Some(start) if executions.len() - start >= options.recent.max(1) => (baseline_ms, start),
+        _ => find_drift(&executions, options)?,
+    };
+    let slow = &executions[start..];
+
+    let durations: Vec<u64> = slow.iter().map(|execution| execution.duration_ms).collect();
+    let current_ms = median(&durations);
+    Some(DurationRegression {
+        id: test.id.clone(),
+        name: test.name.clone(),
+        path: test.path.clone(),
+        baseline_ms,
+        current_ms,
+        increase_percent: (current_ms - baseline_ms) * 100.0 / baseline_ms.max(1.0),
+        executions: slow.len(),
+        started_at: slow[0].timestamp.clone(),
+        start_run_id: slow[0].run_id.clone(),
+    })
+}
+
+/// Compares the most recent executions to the first `window` executions of the history, and
+/// returns the reference median and the start of the trailing slow executions.
+fn find_drift(executions: &[&TestExecution], options: &PerfOptions) -> Option<(f64, usize)> {
+    let reference_len = options.window.min(executions.len());
+    let reference: Vec<u64> = executions[..reference_len]
+        .iter()
+        .map(|execution| execution.duration_ms)
+        .collect();
+    if reference.len() < options.min_baseline {
+        return None;
+    }
+
+    let slow_count = executions[reference_len..]
+        .iter()
+        .rev()
+        .take_while(|execution| is_slow(execution.duration_ms, &reference, options))
+        .count();
+    (slow_count >= options.recent.max(1))
+        .then(|| (median(&reference), executions.len() - slow_count))
+}
+
+fn is_slow(duration_ms: u64, baseline: &[u64], options: &PerfOptions) -> bool {
+    let center = median(baseline);
+    let scale = median_absolute_deviation(baseline, center) * MAD_SCALE;
+    let increase = duration_ms as f64 - center;
+
+    increase >= options.min_increase_ms as f64
+        && increase * 100.0 >= center * options.min_increase_percent
+        && (scale == 0.0 || increase / scale >= options.threshold)
+}
+
+fn format_text(regressions: &[DurationRegression]) -> String {
+    if regressions.is_empty() {
+        return "No duration regressions.\n".to_string();
+    }
+
+    let mut text = format!("Duration regressions ({}):\n", regressions.len());
+    for regression in regressions {
+        text.push_str(&format!(
+            "  {}: {:.0} ms -> {:.0} ms (+{:.0}%) in the last {} executions, since {}{}\n",
+            test_label(&regression.path, &regression.name),
+            regression.baseline_ms,
+            regression.current_ms,
+            regression.increase_percent,
+            regression.executions,
+            regression.started_at,
+            regression
+                .start_run_id
+                .as_ref()
+                .map(|run_id| format!(" (run {})", run_id))
+                .unwrap_or_default()
+        ));
+    }
+    text
+}
+
+fn format_markdown(regressions: &[DurationRegression]) -> String {
+    let mut markdown = String::from(
+        "## Duration regressions\n\n| Test | Baseline | Current | Increase | Executions | Started | Run |\n\
+         | --- | ---: | ---: | ---: | ---: | --- | --- |\n",
+    );
+    for regression in regressions {
+        markdown.push_str(&format!(
+            "| {} | {:.0} ms | {:.0} ms | +{:.0}% | {} | {} | {} |\n",
+            escape_markdown_cell(&test_label(&regression.path, &regression.name)),
+            regression.baseline_ms,
+            regression.current_ms,
+            regression.increase_percent,
+            regression.executions,
+            regression.started_at,
+            escape_markdown_cell(regression.start_run_id.as_deref().unwrap_or("-"))
+        ));
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{create_execution, create_report};
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn create_test(name: &str, durations: &[u64]) -> TestReportTest {
+        TestReportTest {
+            id: name.to_string(),
+            name: name.to_string(),
+            path: "suite".to_string(),
+            executions: durations
+                .iter()
+                .enumerate()
+                .map(|(index, duration_ms)| TestExecution {
+                    timestamp: format!(
+                        "2024-{:02}-{:02}T00:00:00Z",
+                        index / 28 + 1,
+                        index % 28 + 1
+                    ),
+                    duration_ms: *duration_ms,
+                    run_id: Some(format!("run-{}", index + 1)),
+                    ..create_execution(TestExecutionStatus::Passed)
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lasting_slowdowns_are_regressions_worst_first() {
+        let report = create_report(vec![
+            create_test("stable", &[200, 210, 190, 205, 195, 200, 210, 190]),
+            create_test("slower", &[200, 210, 190, 205, 195, 400, 410, 420]),
+            create_test(
+                "much_slower",
+                &[200, 210, 190, 205, 195, 210, 8000, 7900, 8100],
+            ),
+        ]);
+
+        let regressions = find_regressions(&report, &PerfOptions::default());
+
+        let summary: Vec<(&str, f64, f64, usize, Option<&str>)> = regressions
+            .iter()
+            .map(|regression| {
+                (
+                    regression.name.as_str(),
+                    regression.baseline_ms,
+                    regression.current_ms,
+                    regression.executions,
+                    regression.start_run_id.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("much_slower", 202.5, 8000.0, 3, Some("run-7")),
+                ("slower", 200.0, 410.0, 3, Some("run-6")),
+            ]
+        );
+        assert_eq!(regressions[1].increase_percent, 105.0);
+        assert_eq!(regressions[1].started_at, "2024-01-06T00:00:00Z");
+    }
+
+    #[test]
+    fn spikes_and_recovered_slowdowns_are_not_regressions() {
+        let report = create_report(vec![
+            create_test("spike", &[200, 210, 190, 205, 195, 200, 900, 200]),
+            create_test("recovered", &[200, 210, 190, 205, 195, 900, 900, 900, 200]),
+            create_test("short_history", &[200, 210, 900, 900, 900]),
+            create_test("small_increase", &[200, 200, 200, 200, 200, 250, 250, 250]),
+        ]);
+
+        let regressions = find_regressions(&report, &PerfOptions::default());
+
+        assert!(regressions.is_empty());
+    }
+
+    #[test]
+    fn gradual_slowdowns_are_regressions() {
+        let durations: Vec<u64> = (0..40)
+            .map(|index| (200.0 * 1.1_f64.powi(index)).round() as u64)
+            .collect();
+        let report = create_report(vec![create_test("drifting", &durations)]);
+
+        let regressions = find_regressions(&report, &PerfOptions::default());
+
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].name, "drifting");
+        assert_eq!(regressions[0].baseline_ms, 307.5);
+        assert_eq!(regressions[0].started_at, "2024-01-15T00:00:00Z");
+        assert!(regressions[0].current_ms > 2000.0);
+    }
+
+    #[test]
+    fn perf_command_lists_top_regressions() {
+        let mut input_file = NamedTempFile::new().unwrap();
+        let report = create_report(vec![
+            create_test("slower", &[200, 210, 190, 205, 195, 400, 410, 420]),
+            create_test("much_slower", &[200, 210, 190, 205, 195, 8000, 7900, 8100]),
+        ]);
+        input_file
+            .write_all(serde_json::to_string(&report).unwrap().as_bytes())
+            .unwrap();
+        let output_file = NamedTempFile::new().unwrap();
+
+        perf_command(PerfOptions {
+            input: input_file.path().to_str().unwrap().to_string(),
+            output: Some(output_file.path().to_str().unwrap().to_string()),
+            top: 1,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            std::fs::read_to_string(output_file.path()).unwrap(),
+            "Duration regressions (1):\n  suite > much_slower: 200 ms -> 8000 ms (+3900%) \
+             in the last 3 executions, since 2024-01-06T00:00:00Z (run run-6)\n"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use crate::data_filter::DataFilter;
+use crate::input_paths::STDIO_PATH;
+use crate::output_format::test_label;
+use crate::report_io::{read_report, write_output, write_report};
+use crate::test_report::TestReport;
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum QueryFormat {
+    /// The matching tests as a new report.
+    #[default]
+    Report,
+    /// A table with the latest execution of the matching tests.
+    Table,
+}
+
+#[derive(Debug, Clone)]
+pub struct QueryOptions {
+    pub input: String,
+    pub output: Option<String>,
+    pub filter: DataFilter,
+    pub format: QueryFormat,
+}
+
+pub fn query_command(options: QueryOptions) {
+    let report = read_report(&options.input).expect("Failed to read input file");
+
+    let result = query_report(&report, &options.filter);
+    match options.format {
+        QueryFormat::Report => {
+            let output_path = options.output.as_deref().unwrap_or(STDIO_PATH);
+            write_report(&result, output_path).expect("Failed to write report");
+        }
+        QueryFormat::Table => write_output(&format_table(&result), options.output.as_deref())
+            .expect("Failed to write the table"),
+    }
+}
+
+pub fn query_report(report: &TestReport, filter: &DataFilter) -> TestReport {
+    let tests = report
+        .tests
+        .iter()
+        .filter(|test| filter.matches(test))
+        .cloned()
+        .collect();
+    report.with_tests(tests)
+}
+
+fn format_table(report: &TestReport) -> String {
+    let mut rows = vec![[
+        "STATUS".to_string(),
+        "DURATION".to_string(),
+        "TEST".to_string(),
+        "TAGS".to_string(),
+    ]];
+    for test in &report.tests {
+        let execution = test.latest_execution();
+        rows.push([
+            execution
+                .map(|execution| execution.status.as_str().to_string())
+                .unwrap_or_default(),
+            execution
+                .map(|execution| format!("{} ms", execution.duration_ms))
+                .unwrap_or_default(),
+            test_label(&test.path, &test.name),
+            test.tags.as_deref().unwrap_or_default().join(","),
+        ]);
+    }
+
+    let widths: Vec<usize> = (0..3)
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let mut table = String::new();
+    for [status, duration, test, tags] in &rows {
+        let line = format!(
+            "{:<status_width$}  {:>duration_width$}  {:<test_width$}  {}",
+            status,
+            duration,
+            test,
+            tags,
+            status_width = widths[0],
+            duration_width = widths[1],
+            test_width = widths[2]
+        );
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_filter::parse_filter_expression;
+    use crate::test_fixtures::create_execution;
+    use crate::test_report::{
+        REPORT_VERSION, TestExecution, TestExecutionStatus, TestReportSuite, TestReportTest,
+        TestRun,
+    };
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn create_test(name: &str, status: TestExecutionStatus, run_id: &str) -> TestReportTest {
+        TestReportTest {
+            id: name.to_string(),
+            name: name.to_string(),
+            path: "suite".to_string(),
+            executions: vec![TestExecution {
+                run_id: Some(run_id.to_string()),
+                ..create_execution(status)
+            }],
+            tags: Some(vec!["ui".to_string()]),
+            ..Default::default()
+        }
+    }
+
+    fn create_report() -> TestReport {
+        TestReport {
+            version: REPORT_VERSION,
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            runs: ["run-1", "run-2"]
+                .into_iter()
+                .map(|id| TestRun {
+                    id: id.to_string(),
+                    timestamp: "2024-01-01T00:00:00Z".to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            suites: Vec::new(),
+            tests: vec![
+                create_test("passes", TestExecutionStatus::Passed, "run-1"),
+                create_test("fails", TestExecutionStatus::Failed, "run-2"),
+            ],
+        }
+    }
+
+    #[test]
+    fn query_keeps_matching_tests_and_their_runs() {
+        let filter = parse_filter_expression("executions.status == FAILED").unwrap();
+
+        let result = query_report(&create_report(), &filter);
+
+        assert_eq!(result.tests.len(), 1);
+        assert_eq!(result.tests[0].name, "fails");
+        let run_ids: Vec<&str> = result.runs.iter().map(|run| run.id.as_str()).collect();
+        assert_eq!(run_ids, vec!["run-2"]);
+    }
+
+    #[test]
+    fn query_keeps_only_suites_with_matching_tests() {
+        let mut report = create_report();
+        report.suites = [("suite", "run-1"), ("suite", "run-2"), ("other", "run-2")]
+            .into_iter()
+            .map(|(name, run_id)| TestReportSuite {
+                name: name.to_string(),
+                timestamp: "2024-01-01T00:00:00Z".to_string(),
+                status: TestExecutionStatus::Passed,
+                duration_ms: 100,
+                hostname: None,
+                declared_counts: None,
+                actual_counts: Default::default(),
+                errors: Vec::new(),
+                run_id: Some(run_id.to_string()),
+            })
+            .collect();
+        let filter = parse_filter_expression("executions.status == FAILED").unwrap();
+
+        let result = query_report(&report, &filter);
+
+        let suites: Vec<(&str, Option<&str>)> = result
+            .suites
+            .iter()
+            .map(|suite| (suite.name.as_str(), suite.run_id.as_deref()))
+            .collect();
+        assert_eq!(suites, vec![("suite", Some("run-2"))]);
+    }
+
+    #[test]
+    fn query_command_writes_table() {
+        let mut input_file = NamedTempFile::new().unwrap();
+        input_file
+            .write_all(serde_json::to_string(&create_report()).unwrap().as_bytes())
+            .unwrap();
+        let output_file = NamedTempFile::new().unwrap();
+
+        query_command(QueryOptions {
+            input: input_file.path().to_str().unwrap().to_string(),
+            output: Some(output_file.path().to_str().unwrap().to_string()),
+            filter: parse_filter_expression("tags contains ui").unwrap(),
+            format: QueryFormat::Table,
+        });
+
+        assert_eq!(
+            std::fs::read_to_string(output_file.path()).unwrap(),
+            "STATUS  DURATION  TEST            TAGS\n\
+             PASSED    100 ms  suite > passes  ui\n\
+             FAILED    100 ms  suite > fails   ui\n"
+        );
+    }
+}
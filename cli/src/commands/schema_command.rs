@@ -0,0 +1,15 @@
+use crate::input_paths::STDIO_PATH;
+use crate::report_schema::report_schema;
+use std::fs;
+
+pub fn schema_command(output: Option<String>) {
+    let json =
+        serde_json::to_string_pretty(&report_schema()).expect("Failed to serialize the schema");
+
+    match output {
+        Some(path) if path != STDIO_PATH => {
+            fs::write(&path, format!("{}\n", json)).expect("Failed to write schema")
+        }
+        _ => println!("{}", json),
+    }
+}
@@ -0,0 +1,416 @@
+use crate::report_io::{read_report, write_report};
+use crate::test_report::{TestReport, TestReportTest};
+use clap::ValueEnum;
+use glob::Pattern;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum, Serialize)]
+pub enum SplitBy {
+    /// One partition per tag matching the tag pattern.
+    #[default]
+    #[serde(rename = "tag")]
+    Tag,
+    /// One partition per path prefix of the given depth.
+    #[serde(rename = "path")]
+    Path,
+    /// One partition per status of the latest execution.
+    #[serde(rename = "status")]
+    Status,
+}
+
+#[derive(Debug, Clone)]
+pub struct SplitOptions {
+    pub input: String,
+    pub output_dir: String,
+    pub by: SplitBy,
+    pub tag_pattern: String,
+    pub depth: usize,
+    /// Separator of the path segments, detected from the paths if not set.
+    pub path_separator: Option<String>,
+}
+
+impl Default for SplitOptions {
+    fn default() -> Self {
+        SplitOptions {
+            input: String::new(),
+            output_dir: String::new(),
+            by: SplitBy::Tag,
+            tag_pattern: "*".to_string(),
+            depth: 1,
+            path_separator: None,
+        }
+    }
+}
+
+/// Tests of one partition. Tests that belong to no partition have no name.
+#[derive(Clone, Default)]
+pub struct Partition {
+    pub name: Option<String>,
+    pub tests: Vec<TestReportTest>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SplitManifest {
+    #[serde(rename = "splitBy")]
+    pub split_by: SplitBy,
+    pub partitions: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ManifestEntry {
+    /// Tag, path prefix or status of the partition, `null` for the tests that match none.
+    pub name: Option<String>,
+    /// Report file of the partition, relative to the manifest.
+    pub file: String,
+    pub tests: usize,
+}
+
+pub fn split_command(options: SplitOptions) {
+    let report = read_report(&options.input).expect("Failed to read input file");
+    let partitions = partition_report(&report, &options).expect("Invalid tag pattern");
+
+    fs::create_dir_all(&options.output_dir).expect("Failed to create output directory");
+    let mut used_files = HashSet::from([MANIFEST_FILE.to_string()]);
+    let mut entries = Vec::new();
+    for partition in partitions {
+        let file = unique_file_name(partition.name.as_deref(), &mut used_files);
+        let path = Path::new(&options.output_dir).join(&file);
+        let tests = partition.tests.len();
+        write_report(
+            &report.with_tests(partition.tests),
+            path.to_str().expect("Invalid output path"),
+        )
+        .expect("Failed to write report");
+
+        entries.push(ManifestEntry {
+            name: partition.name,
+            file,
+            tests,
+        });
+    }
+
+    let manifest = SplitManifest {
+        split_by: options.by,
+        partitions: entries,
+    };
+    let json = serde_json::to_string_pretty(&manifest).expect("Failed to serialize the manifest");
+    fs::write(Path::new(&options.output_dir).join(MANIFEST_FILE), json)
+        .expect("Failed to write the manifest");
+}
+
+/// Partitions the tests of a report, in the order of the partition names. A test belongs to
+/// every partition of its matching tags, and tests that match no partition come last.
+pub fn partition_report(
+    report: &TestReport,
+    options: &SplitOptions,
+) -> Result<Vec<Partition>, String> {
+    let tag_pattern = Pattern::new(&options.tag_pattern).map_err(|e| e.to_string())?;
+    let path_separator = options
+        .path_separator
+        .clone()
+        .unwrap_or_else(|| detect_path_separator(&report.tests).to_string());
+
+    let mut named: BTreeMap<String, Vec<TestReportTest>> = BTreeMap::new();
+    let mut unmatched = Vec::new();
+    for test in &report.tests {
+        let names = match options.by {
+            SplitBy::Tag => test
+                .tags
+                .iter()
+                .flatten()
+                .filter(|tag| tag_pattern.matches(tag))
+                .cloned()
+                .collect(),
+            SplitBy::Path => path_prefix(&test.path, &path_separator, options.depth)
+                .into_iter()
+                .collect(),
+            SplitBy::Status => test
+                .latest_execution()
+                .map(|execution| execution.status.as_str().to_lowercase())
+                .into_iter()
+                .collect::<Vec<_>>(),
+        };
+
+        if names.is_empty() {
+            unmatched.push(test.clone());
+        }
+        for name in names {
+            named.entry(name).or_default().push(test.clone());
+        }
+    }
+
+    let mut partitions: Vec<Partition> = named
+        .into_iter()
+        .map(|(name, tests)| Partition {
+            name: Some(name),
+            tests,
+        })
+        .collect();
+    if !unmatched.is_empty() {
+        partitions.push(Partition {
+            name: None,
+            tests: unmatched,
+        });
+    }
+    Ok(partitions)
+}
+
+/// `/` if a path contains one, e.g. `e2e/checkout`, otherwise `.` if a path contains one, as
+/// the dotted class names of JUnit, e.g. `com.example.LoginTest`.
+fn detect_path_separator(tests: &[TestReportTest]) -> &'static str {
+    if tests.iter().any(|test| test.path.contains('/')) {
+        "/"
+    } else if tests.iter().any(|test| test.path.contains('.')) {
+        "."
+    } else {
+        "/"
+    }
+}
+
+/// The first `depth` non-empty segments of the path.
+fn path_prefix(path: &str, separator: &str, depth: usize) -> Option<String> {
+    let segments: Vec<&str> = path
+        .split(separator)
+        .filter(|segment| !segment.is_empty())
+        .take(depth.max(1))
+        .collect();
+    (!segments.is_empty()).then(|| segments.join(separator))
+}
+
+fn unique_file_name(name: Option<&str>, used_files: &mut HashSet<String>) -> String {
+    let stem: String = name
+        .unwrap_or("unmatched")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let stem = stem.trim_matches(['-', '.']);
+    let stem = if stem.is_empty() { "partition" } else { stem };
+
+    let mut file = format!("{}.json", stem);
+    let mut suffix = 2;
+    while !used_files.insert(file.clone()) {
+        file = format!("{}-{}.json", stem, suffix);
+        suffix += 1;
+    }
+    file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::create_execution;
+    use crate::test_report::{REPORT_VERSION, TestExecutionStatus};
+    use std::io::Write;
+    use tempfile::{NamedTempFile, tempdir};
+
+    fn create_test(
+        name: &str,
+        path: &str,
+        status: TestExecutionStatus,
+        tags: Vec<&str>,
+    ) -> TestReportTest {
+        TestReportTest {
+            id: name.to_string(),
+            name: name.to_string(),
+            path: path.to_string(),
+            executions: vec![create_execution(status)],
+            tags: (!tags.is_empty()).then(|| tags.into_iter().map(|s| s.to_string()).collect()),
+            ..Default::default()
+        }
+    }
+
+    fn create_report() -> TestReport {
+        TestReport {
+            version: REPORT_VERSION,
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            runs: Vec::new(),
+            suites: Vec::new(),
+            tests: vec![
+                create_test(
+                    "pay",
+                    "shop/payments/card",
+                    TestExecutionStatus::Passed,
+                    vec!["owner:payments", "smoke"],
+                ),
+                create_test(
+                    "ship",
+                    "shop/shipping",
+                    TestExecutionStatus::Failed,
+                    vec!["owner:shipping", "owner:payments"],
+                ),
+                create_test("login", "auth", TestExecutionStatus::Passed, vec!["smoke"]),
+            ],
+        }
+    }
+
+    fn partition_names(partitions: &[Partition]) -> Vec<(Option<&str>, Vec<&str>)> {
+        partitions
+            .iter()
+            .map(|partition| {
+                (
+                    partition.name.as_deref(),
+                    partition
+                        .tests
+                        .iter()
+                        .map(|test| test.name.as_str())
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tests_are_split_by_matching_tags() {
+        let options = SplitOptions {
+            tag_pattern: "owner:*".to_string(),
+            ..Default::default()
+        };
+
+        let partitions = partition_report(&create_report(), &options).unwrap();
+
+        assert_eq!(
+            partition_names(&partitions),
+            vec![
+                (Some("owner:payments"), vec!["pay", "ship"]),
+                (Some("owner:shipping"), vec!["ship"]),
+                (None, vec!["login"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn tests_are_split_by_path_prefix() {
+        let options = SplitOptions {
+            by: SplitBy::Path,
+            depth: 2,
+            ..Default::default()
+        };
+
+        let partitions = partition_report(&create_report(), &options).unwrap();
+
+        assert_eq!(
+            partition_names(&partitions),
+            vec![
+                (Some("auth"), vec!["login"]),
+                (Some("shop/payments"), vec!["pay"]),
+                (Some("shop/shipping"), vec!["ship"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn dotted_paths_are_split_by_package() {
+        let mut report = create_report();
+        for (test, path) in report.tests.iter_mut().zip([
+            "com.example.shop.PaymentTest",
+            "com.example.shop.ShippingTest",
+            "com.example.auth.LoginTest",
+        ]) {
+            test.path = path.to_string();
+        }
+        let options = SplitOptions {
+            by: SplitBy::Path,
+            depth: 3,
+            ..Default::default()
+        };
+
+        let partitions = partition_report(&report, &options).unwrap();
+
+        assert_eq!(
+            partition_names(&partitions),
+            vec![
+                (Some("com.example.auth"), vec!["login"]),
+                (Some("com.example.shop"), vec!["pay", "ship"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn tests_are_split_by_status() {
+        let options = SplitOptions {
+            by: SplitBy::Status,
+            ..Default::default()
+        };
+
+        let partitions = partition_report(&create_report(), &options).unwrap();
+
+        assert_eq!(
+            partition_names(&partitions),
+            vec![
+                (Some("failed"), vec!["ship"]),
+                (Some("passed"), vec!["pay", "login"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn file_names_are_sanitized_and_unique() {
+        let mut used_files = HashSet::from([MANIFEST_FILE.to_string()]);
+
+        let files: Vec<String> = [
+            Some("owner:payments"),
+            Some("owner/payments"),
+            Some("manifest"),
+            Some("../"),
+            None,
+        ]
+        .into_iter()
+        .map(|name| unique_file_name(name, &mut used_files))
+        .collect();
+
+        assert_eq!(
+            files,
+            vec![
+                "owner-payments.json",
+                "owner-payments-2.json",
+                "manifest-2.json",
+                "partition.json",
+                "unmatched.json",
+            ]
+        );
+    }
+
+    #[test]
+    fn split_command_writes_reports_and_manifest() {
+        let mut input_file = NamedTempFile::new().unwrap();
+        input_file
+            .write_all(serde_json::to_string(&create_report()).unwrap().as_bytes())
+            .unwrap();
+        let output_dir = tempdir().unwrap();
+
+        split_command(SplitOptions {
+            input: input_file.path().to_str().unwrap().to_string(),
+            output_dir: output_dir.path().to_str().unwrap().to_string(),
+            tag_pattern: "smoke".to_string(),
+            ..Default::default()
+        });
+
+        let manifest: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(output_dir.path().join(MANIFEST_FILE)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            manifest,
+            serde_json::json!({
+                "splitBy": "tag",
+                "partitions": [
+                    {"name": "smoke", "file": "smoke.json", "tests": 2},
+                    {"name": null, "file": "unmatched.json", "tests": 1}
+                ]
+            })
+        );
+        let smoke = read_report(output_dir.path().join("smoke.json").to_str().unwrap()).unwrap();
+        assert_eq!(smoke.tests.len(), 2);
+    }
+}
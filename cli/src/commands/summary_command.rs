@@ -0,0 +1,553 @@
+use crate::output_format::{OutputFormat, escape_markdown_cell, test_label};
+use crate::report_io::{read_report, write_output};
+use crate::report_stats::{StatusCounts, errored_suites, percentile, total_duration_ms};
+use crate::test_report::{TestExecutionStatus, TestReport, TestReportTest};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default)]
+pub struct SummaryOptions {
+    pub input: String,
+    pub output: Option<String>,
+    pub format: OutputFormat,
+    /// Number of slowest tests to list.
+    pub top: usize,
+    pub group_by_tag: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DurationPercentiles {
+    pub p50: u64,
+    pub p90: u64,
+    pub p95: u64,
+    pub p99: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SummaryTest {
+    pub name: String,
+    pub path: String,
+    pub status: TestExecutionStatus,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathFailures {
+    pub path: String,
+    pub tests: Vec<SummaryTest>,
+}
+
+/// A suite with errors that no test case accounts for, e.g. a crashed `@BeforeAll`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErroredSuite {
+    pub name: String,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TagSummary {
+    pub tag: String,
+    pub counts: StatusCounts,
+    /// None if no test ran.
+    #[serde(rename = "passRate")]
+    pub pass_rate: Option<f64>,
+}
+
+/// Statistics of the latest execution of every test in a report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunSummary {
+    pub tests: u64,
+    pub counts: StatusCounts,
+    /// Errored suites count as failed tests. None if nothing ran.
+    #[serde(rename = "passRate")]
+    pub pass_rate: Option<f64>,
+    #[serde(rename = "erroredSuites")]
+    pub errored_suites: Vec<ErroredSuite>,
+    #[serde(rename = "totalDurationMs")]
+    pub total_duration_ms: u64,
+    #[serde(rename = "durationPercentilesMs")]
+    pub duration_percentiles_ms: DurationPercentiles,
+    pub slowest: Vec<SummaryTest>,
+    #[serde(rename = "failuresByPath")]
+    pub failures_by_path: Vec<PathFailures>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<TagSummary>>,
+}
+
+pub fn summary_command(options: SummaryOptions) {
+    let report = read_report(&options.input).expect("Failed to read input file");
+
+    let summary = summarize(&report, options.top, options.group_by_tag);
+    let content = match options.format {
+        OutputFormat::Text => format_text(&summary),
+        OutputFormat::Json => format!(
+            "{}\n",
+            serde_json::to_string_pretty(&summary).expect("Failed to serialize the summary")
+        ),
+        OutputFormat::Markdown => format_markdown(&summary),
+    };
+    write_output(&content, options.output.as_deref()).expect("Failed to write the summary");
+}
+
+pub fn summarize(report: &TestReport, top: usize, group_by_tag: bool) -> RunSummary {
+    let tests: Vec<SummaryTest> = report.tests.iter().filter_map(summary_test).collect();
+    let counts = StatusCounts::from_tests(&report.tests);
+    let errored_suites: Vec<ErroredSuite> = errored_suites(&report.suites)
+        .into_iter()
+        .map(|suite| ErroredSuite {
+            name: suite.name.clone(),
+            errors: suite.errors.clone(),
+        })
+        .collect();
+
+    let mut durations: Vec<u64> = tests.iter().map(|test| test.duration_ms).collect();
+    durations.sort_unstable();
+
+    let mut slowest = tests.clone();
+    slowest.sort_by_key(|test| Reverse(test.duration_ms));
+    slowest.truncate(top);
+
+    let mut failures_by_path: BTreeMap<&str, Vec<SummaryTest>> = BTreeMap::new();
+    for test in tests.iter().filter(|test| test.status.is_failure()) {
+        failures_by_path
+            .entry(test.path.as_str())
+            .or_default()
+            .push(test.clone());
+    }
+
+    RunSummary {
+        tests: counts.total(),
+        counts,
+        pass_rate: counts.pass_rate(errored_suites.len() as u64),
+        errored_suites,
+        total_duration_ms: total_duration_ms(&report.tests),
+        duration_percentiles_ms: DurationPercentiles {
+            p50: percentile(&durations, 50.0),
+            p90: percentile(&durations, 90.0),
+            p95: percentile(&durations, 95.0),
+            p99: percentile(&durations, 99.0),
+        },
+        slowest,
+        failures_by_path: failures_by_path
+            .into_iter()
+            .map(|(path, tests)| PathFailures {
+                path: path.to_string(),
+                tests,
+            })
+            .collect(),
+        tags: group_by_tag.then(|| tag_summaries(&report.tests)),
+    }
+}
+
+fn summary_test(test: &TestReportTest) -> Option<SummaryTest> {
+    test.latest_execution().map(|execution| SummaryTest {
+        name: test.name.clone(),
+        path: test.path.clone(),
+        status: execution.status.clone(),
+        duration_ms: execution.duration_ms,
+        message: execution.message.clone(),
+    })
+}
+
+fn tag_summaries(tests: &[TestReportTest]) -> Vec<TagSummary> {
+    let mut tests_by_tag: BTreeMap<&str, Vec<&TestReportTest>> = BTreeMap::new();
+    for test in tests {
+        for tag in test.tags.iter().flatten() {
+            tests_by_tag.entry(tag.as_str()).or_default().push(test);
+        }
+    }
+
+    tests_by_tag
+        .into_iter()
+        .map(|(tag, tests)| {
+            let counts = StatusCounts::from_tests(tests);
+            TagSummary {
+                tag: tag.to_string(),
+                counts,
+                pass_rate: counts.pass_rate(0),
+            }
+        })
+        .collect()
+}
+
+fn counts_label(counts: &StatusCounts) -> String {
+    format!(
+        "{} passed, {} skipped, {} failed, {} error",
+        counts.passed, counts.skipped, counts.failed, counts.error
+    )
+}
+
+fn pass_rate_label(pass_rate: Option<f64>) -> String {
+    pass_rate.map_or("n/a".to_string(), |pass_rate| format!("{:.1}%", pass_rate))
+}
+
+fn format_text(summary: &RunSummary) -> String {
+    let percentiles = &summary.duration_percentiles_ms;
+    let mut text = format!(
+        "Tests: {} ({})\nPass rate: {}\nDuration: {} ms total, p50 {} ms, p90 {} ms, p95 {} ms, p99 {} ms\n",
+        summary.tests,
+        counts_label(&summary.counts),
+        pass_rate_label(summary.pass_rate),
+        summary.total_duration_ms,
+        percentiles.p50,
+        percentiles.p90,
+        percentiles.p95,
+        percentiles.p99
+    );
+
+    if !summary.errored_suites.is_empty() {
+        text.push_str("\nErrored suites:\n");
+        for suite in &summary.errored_suites {
+            text.push_str(&format!("  {}\n", suite.name));
+            for error in &suite.errors {
+                text.push_str(&format!("    {}\n", error));
+            }
+        }
+    }
+
+    if !summary.slowest.is_empty() {
+        text.push_str("\nSlowest tests:\n");
+        for test in &summary.slowest {
+            text.push_str(&format!(
+                "  {} ms  {}\n",
+                test.duration_ms,
+                test_label(&test.path, &test.name)
+            ));
+        }
+    }
+
+    if !summary.failures_by_path.is_empty() {
+        text.push_str("\nFailures by path:\n");
+        for failures in &summary.failures_by_path {
+            text.push_str(&format!("  {} ({})\n", failures.path, failures.tests.len()));
+            for test in &failures.tests {
+                text.push_str(&format!("    {}: {}\n", test.name, test.status.as_str()));
+            }
+        }
+    }
+
+    if let Some(tags) = &summary.tags {
+        text.push_str("\nTags:\n");
+        if tags.is_empty() {
+            text.push_str("  No tagged tests\n");
+        }
+        for tag in tags {
+            text.push_str(&format!(
+                "  {}: {} tests ({}), pass rate {}\n",
+                tag.tag,
+                tag.counts.total(),
+                counts_label(&tag.counts),
+                pass_rate_label(tag.pass_rate)
+            ));
+        }
+    }
+
+    text
+}
+
+fn format_markdown(summary: &RunSummary) -> String {
+    let counts = &summary.counts;
+    let percentiles = &summary.duration_percentiles_ms;
+    let mut markdown = format!(
+        "## Test summary\n\n\
+         | Tests | Passed | Skipped | Failed | Error | Pass rate | Duration |\n\
+         | ---: | ---: | ---: | ---: | ---: | ---: | ---: |\n\
+         | {} | {} | {} | {} | {} | {} | {} ms |\n\n\
+         Duration percentiles: p50 {} ms, p90 {} ms, p95 {} ms, p99 {} ms\n",
+        summary.tests,
+        counts.passed,
+        counts.skipped,
+        counts.failed,
+        counts.error,
+        pass_rate_label(summary.pass_rate),
+        summary.total_duration_ms,
+        percentiles.p50,
+        percentiles.p90,
+        percentiles.p95,
+        percentiles.p99
+    );
+
+    if !summary.errored_suites.is_empty() {
+        markdown.push_str("\n### Errored suites\n\n| Suite | Errors |\n| --- | --- |\n");
+        for suite in &summary.errored_suites {
+            markdown.push_str(&format!(
+                "| {} | {} |\n",
+                escape_markdown_cell(&suite.name),
+                escape_markdown_cell(&suite.errors.join("; "))
+            ));
+        }
+    }
+
+    if !summary.slowest.is_empty() {
+        markdown.push_str(
+            "\n### Slowest tests\n\n| Test | Status | Duration |\n| --- | --- | ---: |\n",
+        );
+        for test in &summary.slowest {
+            markdown.push_str(&format!(
+                "| {} | {} | {} ms |\n",
+                escape_markdown_cell(&test_label(&test.path, &test.name)),
+                test.status.as_str(),
+                test.duration_ms
+            ));
+        }
+    }
+
+    if !summary.failures_by_path.is_empty() {
+        markdown
+            .push_str("\n### Failures by path\n\n| Path | Test | Status |\n| --- | --- | --- |\n");
+        for failures in &summary.failures_by_path {
+            for test in &failures.tests {
+                markdown.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    escape_markdown_cell(&failures.path),
+                    escape_markdown_cell(&test.name),
+                    test.status.as_str()
+                ));
+            }
+        }
+    }
+
+    if let Some(tags) = &summary.tags {
+        markdown.push_str(
+            "\n### Tags\n\n| Tag | Tests | Passed | Skipped | Failed | Error | Pass rate |\n\
+             | --- | ---: | ---: | ---: | ---: | ---: | ---: |\n",
+        );
+        for tag in tags {
+            markdown.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {} |\n",
+                escape_markdown_cell(&tag.tag),
+                tag.counts.total(),
+                tag.counts.passed,
+                tag.counts.skipped,
+                tag.counts.failed,
+                tag.counts.error,
+                pass_rate_label(tag.pass_rate)
+            ));
+        }
+    }
+
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{create_execution, create_suite};
+    use crate::test_report::{REPORT_VERSION, TestExecution};
+    use std::fs;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn create_test(
+        name: &str,
+        path: &str,
+        status: TestExecutionStatus,
+        duration_ms: u64,
+        tags: Vec<&str>,
+    ) -> TestReportTest {
+        TestReportTest {
+            id: name.to_string(),
+            name: name.to_string(),
+            path: path.to_string(),
+            executions: vec![TestExecution {
+                duration_ms,
+                ..create_execution(status)
+            }],
+            tags: (!tags.is_empty()).then(|| tags.into_iter().map(|s| s.to_string()).collect()),
+            ..Default::default()
+        }
+    }
+
+    fn create_report() -> TestReport {
+        TestReport {
+            version: REPORT_VERSION,
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            runs: Vec::new(),
+            suites: Vec::new(),
+            tests: vec![
+                create_test("a", "Login", TestExecutionStatus::Passed, 10, vec!["ui"]),
+                create_test("b", "Login", TestExecutionStatus::Failed, 300, vec!["ui"]),
+                create_test("c", "Cart", TestExecutionStatus::Error, 20, vec![]),
+                create_test("d", "Cart", TestExecutionStatus::Skipped, 0, vec!["ui"]),
+                create_test("e", "Cart", TestExecutionStatus::Passed, 70, vec![]),
+            ],
+        }
+    }
+
+    #[test]
+    fn summary_counts_statuses_and_durations() {
+        let summary = summarize(&create_report(), 2, false);
+
+        assert_eq!(summary.tests, 5);
+        assert_eq!(
+            summary.counts,
+            StatusCounts {
+                passed: 2,
+                skipped: 1,
+                failed: 1,
+                error: 1,
+            }
+        );
+        assert_eq!(summary.pass_rate, Some(50.0));
+        assert_eq!(summary.total_duration_ms, 400);
+        assert_eq!(
+            summary.duration_percentiles_ms,
+            DurationPercentiles {
+                p50: 20,
+                p90: 300,
+                p95: 300,
+                p99: 300,
+            }
+        );
+        let slowest: Vec<&str> = summary
+            .slowest
+            .iter()
+            .map(|test| test.name.as_str())
+            .collect();
+        assert_eq!(slowest, vec!["b", "e"]);
+        assert_eq!(summary.tags, None);
+    }
+
+    #[test]
+    fn latest_execution_is_ordered_by_time() {
+        let mut report = create_report();
+        report.tests.truncate(1);
+        let passed = report.tests[0].executions[0].clone();
+        report.tests[0].executions = vec![
+            TestExecution {
+                timestamp: "2024-01-01T00:00:00.500Z".to_string(),
+                ..passed.clone()
+            },
+            TestExecution {
+                timestamp: "2024-01-01T00:00:00Z".to_string(),
+                status: TestExecutionStatus::Failed,
+                ..passed
+            },
+        ];
+
+        let summary = summarize(&report, 2, false);
+
+        assert_eq!(summary.counts.passed, 1);
+        assert_eq!(summary.counts.failed, 0);
+    }
+
+    #[test]
+    fn failures_are_grouped_by_path() {
+        let summary = summarize(&create_report(), 10, false);
+
+        let failures: Vec<(&str, Vec<&str>)> = summary
+            .failures_by_path
+            .iter()
+            .map(|failures| {
+                (
+                    failures.path.as_str(),
+                    failures
+                        .tests
+                        .iter()
+                        .map(|test| test.name.as_str())
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(failures, vec![("Cart", vec!["c"]), ("Login", vec!["b"])]);
+    }
+
+    #[test]
+    fn tags_are_summarized_when_grouping_by_tag() {
+        let summary = summarize(&create_report(), 10, true);
+
+        let tags = summary.tags.unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].tag, "ui");
+        assert_eq!(tags[0].counts.total(), 3);
+        assert_eq!(tags[0].pass_rate, Some(50.0));
+    }
+
+    #[test]
+    fn errored_suites_are_listed_and_count_as_failed() {
+        let mut report = create_report();
+        report.tests.clear();
+        report.suites = vec![create_suite(
+            "com.example.SetupTest",
+            "2024-01-01T00:00:00Z",
+            &["Suite declares 1 errors but its test cases report 0"],
+        )];
+
+        let summary = summarize(&report, 1, false);
+
+        assert_eq!(summary.pass_rate, Some(0.0));
+        assert_eq!(
+            format_text(&summary),
+            "Tests: 0 (0 passed, 0 skipped, 0 failed, 0 error)\n\
+             Pass rate: 0.0%\n\
+             Duration: 0 ms total, p50 0 ms, p90 0 ms, p95 0 ms, p99 0 ms\n\
+             \n\
+             Errored suites:\n  com.example.SetupTest\n    \
+             Suite declares 1 errors but its test cases report 0\n"
+        );
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(
+            json["erroredSuites"][0]["errors"][0],
+            "Suite declares 1 errors but its test cases report 0"
+        );
+    }
+
+    #[test]
+    fn pass_rate_is_not_available_when_no_test_ran() {
+        let mut report = create_report();
+        report.tests.retain(|test| test.name == "d");
+
+        let summary = summarize(&report, 1, false);
+
+        assert_eq!(summary.pass_rate, None);
+        assert!(format_text(&summary).contains("Pass rate: n/a\n"));
+        assert_eq!(
+            serde_json::to_value(&summary).unwrap()["passRate"],
+            serde_json::Value::Null
+        );
+    }
+
+    #[test]
+    fn summary_command_writes_json() {
+        let mut input_file = NamedTempFile::new().unwrap();
+        input_file
+            .write_all(serde_json::to_string(&create_report()).unwrap().as_bytes())
+            .unwrap();
+        let output_file = NamedTempFile::new().unwrap();
+
+        summary_command(SummaryOptions {
+            input: input_file.path().to_str().unwrap().to_string(),
+            output: Some(output_file.path().to_str().unwrap().to_string()),
+            format: OutputFormat::Json,
+            top: 1,
+            group_by_tag: false,
+        });
+
+        let result: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(output_file.path()).unwrap()).unwrap();
+        assert_eq!(result["counts"]["failed"], 1);
+        assert_eq!(result["passRate"], 50.0);
+        assert_eq!(result["slowest"][0]["durationMs"], 300);
+        assert_eq!(result["failuresByPath"][0]["path"], "Cart");
+    }
+
+    #[test]
+    fn text_lists_counts_slowest_tests_and_failures() {
+        let text = format_text(&summarize(&create_report(), 1, false));
+
+        assert_eq!(
+            text,
+            "Tests: 5 (2 passed, 1 skipped, 1 failed, 1 error)\n\
+             Pass rate: 50.0%\n\
+             Duration: 400 ms total, p50 20 ms, p90 300 ms, p95 300 ms, p99 300 ms\n\
+             \n\
+             Slowest tests:\n  300 ms  Login > b\n\
+             \n\
+             Failures by path:\n  Cart (1)\n    c: ERROR\n  Login (1)\n    b: FAILED\n"
+        );
+    }
+}
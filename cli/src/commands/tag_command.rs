@@ -1,140 +1,88 @@
-﻿use crate::test_report::TestReport;
+﻿use crate::report_io::{read_report, write_report};
+use crate::tag_matcher::{TagMatcher, TextPattern, parse_tag_matcher};
+use crate::tag_rules::{TagOperation, TagRule, apply_tag_rules};
 use regex::Regex;
-use std::fs;
 
-enum TagOperation {
-    Add,
-    Remove,
-    Update,
-}
-
-struct ParsedExpression {
-    expression: Regex,
-    operation: TagOperation,
-    tags: Vec<String>,
-}
-
-pub fn tag_command(input: String, output: Option<String>, tags: Vec<String>) {
-    let mut report = read_report(&input);
-    let tag_expressions = parse_tag_expressions(tags);
-    apply_tags(&mut report, &tag_expressions);
+/// Applies the tag rules in order, i.e. those of a rules file, then the `--tag` expressions.
+pub fn tag_command(input: String, output: Option<String>, rules: Vec<TagRule>) {
+    let mut report = read_report(&input).expect("Failed to read input file");
+    apply_tag_rules(&mut report.tests, &rules);
     let output_path = output.unwrap_or(input);
-    write_report(&report, &output_path);
+    write_report(&report, &output_path).expect("Failed to write report");
 }
 
-fn read_report(input_path: &str) -> TestReport {
-    let data = fs::read_to_string(input_path).expect("Failed to read input file");
-    serde_json::from_str(&data).expect("Failed to parse input file")
-}
-
-fn parse_tag_expressions(tags: Vec<String>) -> Vec<ParsedExpression> {
-    tags.into_iter()
-        .filter_map(|tag| {
-            let parts: Vec<&str> = tag.splitn(3, ':').collect();
-            if parts.len() != 3 {
-                return None;
-            }
-
-            let expression = parts[0].to_string();
-            if expression.is_empty() {
-                return None;
-            }
-
-            let operation = match parts[1] {
-                "add" => TagOperation::Add,
-                "remove" => TagOperation::Remove,
-                "update" => TagOperation::Update,
-                _ => return None,
-            };
-
-            let tags_str = parts[2];
-            if tags_str.is_empty()
-                && (matches!(operation, TagOperation::Add)
-                    || matches!(operation, TagOperation::Remove))
-            {
-                return None;
-            }
-
-            let tags = tags_str
-                .split(',')
-                .map(|s| s.to_string())
-                .filter(|s| !s.is_empty())
-                .collect();
-
-            Regex::new(&expression).ok().map(|re| ParsedExpression {
-                expression: re,
-                operation,
-                tags,
-            })
-        })
-        .collect()
-}
-
-fn apply_tags(report: &mut TestReport, tag_expressions: &[ParsedExpression]) {
-    for test in &mut report.tests {
-        for parsed_expression in tag_expressions {
-            let ParsedExpression {
-                expression,
-                operation,
-                tags,
-            } = parsed_expression;
-
-            if expression.is_match(&test.path) {
-                test.tags = match operation {
-                    TagOperation::Add => add_tags_to_test(test.tags.as_ref(), tags),
-                    TagOperation::Remove => remove_tags_from_test(test.tags.as_ref(), tags),
-                    TagOperation::Update => update_tags_in_test(tags),
-                };
-            }
+/// Parses `--tag` expressions of the form `expression:operation:tag1,tag2`. An expression without
+/// a matcher operator is a path regex.
+pub fn parse_tag_expressions(tags: Vec<String>) -> Result<Vec<TagRule>, String> {
+    let mut rules = Vec::new();
+    for tag in tags {
+        let parts: Vec<&str> = tag.splitn(3, ':').collect();
+        if parts.len() != 3 {
+            continue;
         }
-    }
-}
 
-fn add_tags_to_test(
-    current_tags: Option<&Vec<String>>,
-    tags_to_add: &[String],
-) -> Option<Vec<String>> {
-    let mut new_tags = current_tags.cloned().unwrap_or_default();
-    for tag in tags_to_add {
-        if !new_tags.contains(tag) {
-            new_tags.push(tag.clone());
+        let expression = parts[0];
+        if expression.is_empty() {
+            continue;
         }
-    }
-    Some(new_tags)
-}
 
-fn remove_tags_from_test(
-    current_tags: Option<&Vec<String>>,
-    tags_to_remove: &[String],
-) -> Option<Vec<String>> {
-    current_tags.map(|tags| {
-        tags.iter()
-            .filter(|tag| !tags_to_remove.contains(tag))
-            .cloned()
-            .collect()
-    })
-}
+        let operation = match parts[1] {
+            "add" => TagOperation::Add,
+            "remove" => TagOperation::Remove,
+            "update" => TagOperation::Update,
+            _ => continue,
+        };
+
+        let tags_str = parts[2];
+        if tags_str.is_empty()
+            && (matches!(operation, TagOperation::Add) || matches!(operation, TagOperation::Remove))
+        {
+            continue;
+        }
 
-fn update_tags_in_test(tags_to_update: &[String]) -> Option<Vec<String>> {
-    Some(tags_to_update.to_vec())
+        let tags = tags_str
+            .split(',')
+            .map(|s| s.to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let matcher = if has_matcher_operator(expression) {
+            parse_tag_matcher(expression)
+                .map_err(|e| format!("Invalid tag expression {}: {}", tag, e))?
+        } else {
+            Regex::new(expression)
+                .map(|regex| TagMatcher::Path(TextPattern::Regex(regex)))
+                .map_err(|e| format!("Invalid tag expression {}: {}", tag, e))?
+        };
+        rules.push(TagRule {
+            matcher,
+            operation,
+            tags,
+        });
+    }
+    Ok(rules)
 }
 
-fn write_report(report: &TestReport, output_path: &str) {
-    let json = serde_json::to_string_pretty(report).expect("Failed to serialize report");
-    fs::write(output_path, json).expect("Failed to write report");
+fn has_matcher_operator(expression: &str) -> bool {
+    ["==", "!=", "~", "<", ">"]
+        .iter()
+        .any(|operator| expression.contains(operator))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_fixtures::{create_execution, create_report};
+    use crate::test_identity::test_id;
     use crate::test_report::{TestExecution, TestExecutionStatus, TestReport, TestReportTest};
     use parameterized::parameterized;
+    use std::fs;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
     #[test]
     fn add_tags_to_test_without_tags() {
-        let report = create_test_report(vec![create_test_without_tags("test1", "path/to/test1")]);
+        let report = create_report(vec![create_test_without_tags("test1", "path/to/test1")]);
         let (_, _, result_report) = setup_test(&report, vec!["test1:add:tag1,tag2".to_string()]);
 
         assert_eq!(
@@ -145,7 +93,7 @@ mod tests {
 
     #[test]
     fn add_tags_to_test_with_existing_tags() {
-        let report = create_test_report(vec![create_test_with_tags(
+        let report = create_report(vec![create_test_with_tags(
             "test2",
             "path/to/test2",
             vec!["existing_tag", "another_tag"],
@@ -164,7 +112,7 @@ mod tests {
 
     #[test]
     fn add_existing_tag_does_not_duplicate() {
-        let report = create_test_report(vec![create_test_with_tags(
+        let report = create_report(vec![create_test_with_tags(
             "test2",
             "path/to/test2",
             vec!["existing_tag", "another_tag"],
@@ -179,7 +127,7 @@ mod tests {
 
     #[test]
     fn remove_existing_tag() {
-        let report = create_test_report(vec![create_test_with_tags(
+        let report = create_report(vec![create_test_with_tags(
             "test2",
             "path/to/test2",
             vec!["existing_tag", "another_tag"],
@@ -195,7 +143,7 @@ mod tests {
 
     #[test]
     fn remove_non_existing_tag() {
-        let report = create_test_report(vec![create_test_with_tags(
+        let report = create_report(vec![create_test_with_tags(
             "test2",
             "path/to/test2",
             vec!["existing_tag", "another_tag"],
@@ -211,7 +159,7 @@ mod tests {
 
     #[test]
     fn update_tags_replaces_existing_tags() {
-        let report = create_test_report(vec![create_test_with_tags(
+        let report = create_report(vec![create_test_with_tags(
             "test2",
             "path/to/test2",
             vec!["existing_tag", "another_tag"],
@@ -227,7 +175,7 @@ mod tests {
 
     #[test]
     fn multiple_expressions_are_applied_correctly() {
-        let report = create_test_report(vec![
+        let report = create_report(vec![
             create_test_without_tags("test1", "path/to/test1"),
             create_test_with_tags(
                 "test2",
@@ -261,7 +209,7 @@ mod tests {
         }
     )]
     fn invalid_expression_is_ignored(expression: &str) {
-        let report = create_test_report(vec![
+        let report = create_report(vec![
             create_test_without_tags("test1", "path/to/test1"),
             create_test_with_tags(
                 "test2",
@@ -280,7 +228,7 @@ mod tests {
 
     #[test]
     fn regex_expression_is_applied_correctly() {
-        let report = create_test_report(vec![
+        let report = create_report(vec![
             create_test_without_tags("test1", "path/to/test1"),
             create_test_with_tags("test2", "path/to/another/test2", vec!["existing_tag"]),
         ]);
@@ -295,7 +243,7 @@ mod tests {
 
     #[test]
     fn regex_match_all_expression_is_applied_correctly() {
-        let report = create_test_report(vec![
+        let report = create_report(vec![
             create_test_without_tags("test1", "path/to/test1"),
             create_test_with_tags("test2", "path/to/another/test2", vec!["existing_tag"]),
         ]);
@@ -308,9 +256,96 @@ mod tests {
         );
     }
 
+    #[test]
+    fn matcher_expression_is_applied_correctly() {
+        let report = create_report(vec![
+            create_test_without_tags("test1", "path/to/test1"),
+            create_test_with_tags("test2", "path/to/test2", vec!["existing_tag"]),
+            create_test_with_tags("test3", "path/to/test3", vec!["quarantined"]),
+        ]);
+        let (_, _, result_report) = setup_test(
+            &report,
+            vec![
+                "status == failed and duration >= 200 and not tag == quarantined:add:triage"
+                    .to_string(),
+                "message ~ fail or name == test1:add:seen".to_string(),
+            ],
+        );
+
+        assert_eq!(result_report.tests[0].tags, Some(vec!["seen".to_string()]));
+        assert_eq!(
+            result_report.tests[1].tags,
+            Some(vec![
+                "existing_tag".to_string(),
+                "triage".to_string(),
+                "seen".to_string()
+            ])
+        );
+        assert_eq!(
+            result_report.tests[2].tags,
+            Some(vec!["quarantined".to_string(), "seen".to_string()])
+        );
+    }
+
+    #[test]
+    fn regex_expression_is_matched_against_the_path() {
+        let report = create_report(vec![create_test_without_tags("test1", "suite/login")]);
+        let (_, _, result_report) = setup_test(
+            &report,
+            vec![
+                "^test1$:add:by_name".to_string(),
+                "^suite/:add:by_path".to_string(),
+            ],
+        );
+
+        assert_eq!(
+            result_report.tests[0].tags,
+            Some(vec!["by_path".to_string()])
+        );
+    }
+
+    #[parameterized(tag = {
+        "status == faild:add:bad",
+        "duration > 5 sec:add:slow",
+        "name == test1 and:add:broken",
+        "path/(to:add:unclosed",
+    })]
+    fn invalid_expressions_are_errors(tag: &str) {
+        let error = parse_tag_expressions(vec![tag.to_string()]).unwrap_err();
+
+        assert!(error.starts_with(&format!("Invalid tag expression {}: ", tag)));
+    }
+
+    #[test]
+    fn rules_are_applied_before_expressions() {
+        let report = create_report(vec![create_test_without_tags("test1", "path/to/test1")]);
+        let mut input_file = NamedTempFile::new().unwrap();
+        let json = serde_json::to_string(&report).unwrap();
+        input_file.write_all(json.as_bytes()).unwrap();
+
+        let mut rules = vec![
+            TagRule::new(
+                "path ~ '^path/'",
+                TagOperation::Add,
+                vec!["owner:auth".to_string()],
+            )
+            .unwrap(),
+        ];
+        rules.extend(parse_tag_expressions(vec!["tag ~ ^owner:add:reviewed".to_string()]).unwrap());
+        tag_command(input_file.path().to_str().unwrap().to_string(), None, rules);
+
+        let result_data = fs::read_to_string(input_file.path()).unwrap();
+        let result_report: TestReport = serde_json::from_str(&result_data).unwrap();
+
+        assert_eq!(
+            result_report.tests[0].tags,
+            Some(vec!["owner:auth".to_string(), "reviewed".to_string()])
+        );
+    }
+
     #[test]
     fn no_output_file_overwrites_input_file() {
-        let report = create_test_report(vec![create_test_without_tags("test1", "path/to/test1")]);
+        let report = create_report(vec![create_test_without_tags("test1", "path/to/test1")]);
         let mut input_file = NamedTempFile::new().unwrap();
         let json = serde_json::to_string(&report).unwrap();
         input_file.write_all(json.as_bytes()).unwrap();
@@ -318,7 +353,7 @@ mod tests {
         tag_command(
             input_file.path().to_str().unwrap().to_string(),
             None,
-            vec!["test1:add:tag1".to_string()],
+            parse_tag_expressions(vec!["test1:add:tag1".to_string()]).unwrap(),
         );
 
         let result_data = fs::read_to_string(input_file.path()).unwrap();
@@ -339,7 +374,7 @@ mod tests {
         tag_command(
             input_file.path().to_str().unwrap().to_string(),
             Some(output_file.path().to_str().unwrap().to_string()),
-            tags,
+            parse_tag_expressions(tags).unwrap(),
         );
 
         let result_data = fs::read_to_string(output_file.path()).unwrap();
@@ -348,30 +383,20 @@ mod tests {
         (input_file, output_file, result_report)
     }
 
-    fn create_test_report(tests: Vec<TestReportTest>) -> TestReport {
-        TestReport {
-            version: 1,
-            timestamp: "2024-01-01T00:00:00Z".to_string(),
-            tests,
-        }
-    }
-
     fn create_test_without_tags(name: &str, path: &str) -> TestReportTest {
         TestReportTest {
+            id: test_id(path, name),
             name: name.to_string(),
             path: path.to_string(),
-            executions: vec![TestExecution {
-                timestamp: "2024-01-01T00:00:00Z".to_string(),
-                status: TestExecutionStatus::Passed,
-                duration_ms: 100,
-                message: None,
-            }],
+            executions: vec![create_execution(TestExecutionStatus::Passed)],
             tags: None,
+            ..Default::default()
         }
     }
 
     fn create_test_with_tags(name: &str, path: &str, tags: Vec<&str>) -> TestReportTest {
         TestReportTest {
+            id: test_id(path, name),
             name: name.to_string(),
             path: path.to_string(),
             executions: vec![TestExecution {
@@ -379,8 +404,10 @@ mod tests {
                 status: TestExecutionStatus::Failed,
                 duration_ms: 200,
                 message: Some("failed".to_string()),
+                ..Default::default()
             }],
             tags: Some(tags.into_iter().map(|s| s.to_string()).collect()),
+            ..Default::default()
         }
     }
 }
@@ -0,0 +1,99 @@
+use crate::input_paths::STDIO_PATH;
+use crate::report_schema::validate_report;
+use serde_json::Value;
+use std::fs;
+use std::io::Read;
+
+/// Validates every input against the report schema, prints the errors and returns
+/// whether all inputs are valid.
+pub fn validate_command(inputs: Vec<String>) -> bool {
+    let mut valid = true;
+
+    for input in &inputs {
+        let errors = match validate_file(input) {
+            Ok(errors) => errors,
+            Err(e) => vec![e],
+        };
+
+        if errors.is_empty() {
+            println!("{}: valid", input);
+            continue;
+        }
+
+        valid = false;
+        println!("{}: {} error(s)", input, errors.len());
+        for error in errors {
+            println!("  {}", error);
+        }
+    }
+
+    valid
+}
+
+fn validate_file(input: &str) -> Result<Vec<String>, String> {
+    let data = if input == STDIO_PATH {
+        let mut data = String::new();
+        std::io::stdin()
+            .read_to_string(&mut data)
+            .map_err(|e| format!("I/O error: {}", e))?;
+        data
+    } else {
+        fs::read_to_string(input).map_err(|e| format!("I/O error: {}", e))?
+    };
+
+    let report: Value = serde_json::from_str(&data).map_err(|e| format!("Invalid JSON: {}", e))?;
+    validate_report(&report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn write_file(content: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn valid_file_has_no_errors() {
+        let file =
+            write_file(r#"{"version": 2, "timestamp": "2025-01-06T15:34:21Z", "tests": []}"#);
+
+        let errors = validate_file(file.path().to_str().unwrap()).unwrap();
+
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn invalid_json_is_error() {
+        let file = write_file("{");
+
+        let result = validate_file(file.path().to_str().unwrap());
+
+        assert!(result.unwrap_err().starts_with("Invalid JSON: "));
+    }
+
+    #[test]
+    fn missing_file_is_error() {
+        let result = validate_file("missing-report.json");
+
+        assert!(result.unwrap_err().starts_with("I/O error: "));
+    }
+
+    #[test]
+    fn validate_command_returns_false_if_any_input_is_invalid() {
+        let valid =
+            write_file(r#"{"version": 2, "timestamp": "2025-01-06T15:34:21Z", "tests": []}"#);
+        let invalid = write_file(r#"{"version": 2, "tests": [{}]}"#);
+
+        let result = validate_command(vec![
+            valid.path().to_str().unwrap().to_string(),
+            invalid.path().to_str().unwrap().to_string(),
+        ]);
+
+        assert!(!result);
+    }
+}
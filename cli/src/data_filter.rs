@@ -0,0 +1,608 @@
+use crate::test_report::TestReportTest;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+
+/// Filter over the tests of a report, with the semantics of the `DataFilter` of the dashboard
+/// layouts.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DataFilter {
+    pub operator: LogicalOperator,
+    pub conditions: Vec<FilterNode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum LogicalOperator {
+    #[serde(rename = "AND")]
+    And,
+    #[serde(rename = "OR")]
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum FilterNode {
+    Filter(DataFilter),
+    Condition(Condition),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Condition {
+    pub field: String,
+    pub operator: ConditionOperator,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum ConditionOperator {
+    #[serde(rename = "==", alias = "equals")]
+    Equals,
+    #[serde(rename = "!=", alias = "not equals")]
+    NotEquals,
+    #[serde(rename = "in")]
+    In,
+    #[serde(rename = "not in")]
+    NotIn,
+    #[serde(rename = "contains")]
+    Contains,
+    #[serde(rename = ">=")]
+    GreaterOrEqual,
+    #[serde(rename = ">")]
+    Greater,
+    #[serde(rename = "<")]
+    Less,
+    #[serde(rename = "<=")]
+    LessOrEqual,
+}
+
+impl DataFilter {
+    /// A filter without conditions matches every test.
+    pub fn matches(&self, test: &TestReportTest) -> bool {
+        if self.conditions.is_empty() {
+            return true;
+        }
+
+        let target = FilterTarget {
+            test: serde_json::to_value(test).expect("Failed to serialize the test"),
+            latest_execution: serde_json::to_value(test.latest_execution())
+                .expect("Failed to serialize the execution"),
+        };
+        self.evaluate(&target)
+    }
+
+    fn evaluate(&self, test: &FilterTarget) -> bool {
+        match self.operator {
+            LogicalOperator::And => self.conditions.iter().all(|node| node.evaluate(test)),
+            LogicalOperator::Or => self.conditions.iter().any(|node| node.evaluate(test)),
+        }
+    }
+}
+
+impl FilterNode {
+    fn evaluate(&self, test: &FilterTarget) -> bool {
+        match self {
+            FilterNode::Filter(filter) => filter.evaluate(test),
+            FilterNode::Condition(condition) => condition.evaluate(test),
+        }
+    }
+}
+
+impl Condition {
+    fn evaluate(&self, test: &FilterTarget) -> bool {
+        let test_value = field_value(test, &self.field);
+        let value = &self.value;
+
+        match self.operator {
+            ConditionOperator::Equals => is_equal(test_value, Some(value)),
+            ConditionOperator::NotEquals => !is_equal(test_value, Some(value)),
+            ConditionOperator::In => value
+                .as_array()
+                .is_some_and(|values| values.iter().any(|v| is_equal(test_value, Some(v)))),
+            ConditionOperator::NotIn => !value
+                .as_array()
+                .is_some_and(|values| values.iter().any(|v| is_equal(test_value, Some(v)))),
+            ConditionOperator::Contains => match (test_value, value) {
+                (Some(Value::String(test_value)), Value::String(value)) => {
+                    test_value.contains(value.as_str())
+                }
+                (Some(Value::Array(test_values)), _) => {
+                    test_values.iter().any(|v| is_equal(Some(v), Some(value)))
+                }
+                _ => false,
+            },
+            ConditionOperator::GreaterOrEqual => compare(test_value, value, |a, b| a >= b),
+            ConditionOperator::Greater => compare(test_value, value, |a, b| a > b),
+            ConditionOperator::Less => compare(test_value, value, |a, b| a < b),
+            ConditionOperator::LessOrEqual => compare(test_value, value, |a, b| a <= b),
+        }
+    }
+}
+
+/// A test and its latest execution, as chosen by `TestReportTest::latest_execution`.
+struct FilterTarget {
+    test: Value,
+    latest_execution: Value,
+}
+
+/// Resolves a dotted field of a test. `executions.<field>` is the field of the latest execution.
+fn field_value<'a>(target: &'a FilterTarget, field: &str) -> Option<&'a Value> {
+    if let Some(execution_field) = field.strip_prefix("executions.") {
+        return target
+            .latest_execution
+            .get(execution_field.split('.').next()?);
+    }
+
+    field
+        .split('.')
+        .try_fold(&target.test, |current, part| current.get(part))
+}
+
+/// Missing fields and `null` are equal, numbers are compared by value.
+fn is_equal(a: Option<&Value>, b: Option<&Value>) -> bool {
+    match (a, b) {
+        (None | Some(Value::Null), None | Some(Value::Null)) => true,
+        (Some(Value::Number(a)), Some(Value::Number(b))) => a.as_f64() == b.as_f64(),
+        (Some(Value::Array(a)), Some(Value::Array(b))) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| is_equal(Some(a), Some(b)))
+        }
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn compare(a: Option<&Value>, b: &Value, predicate: fn(f64, f64) -> bool) -> bool {
+    match (a.and_then(Value::as_f64), b.as_f64()) {
+        (Some(a), Some(b)) => predicate(a, b),
+        _ => false,
+    }
+}
+
+/// Reads a filter from a JSON file, in the format of the `filter` of dashboard widgets.
+pub fn read_filter(path: &str) -> Result<DataFilter, String> {
+    let data = fs::read_to_string(path).map_err(|e| format!("I/O error: {}", e))?;
+    serde_json::from_str(&data).map_err(|e| format!("Invalid filter: {}", e))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    OpenParen,
+    CloseParen,
+    OpenBracket,
+    CloseBracket,
+    Comma,
+    Operator(ConditionOperator),
+    Word(String),
+    Value(Value),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::OpenParen => write!(f, "'('"),
+            Token::CloseParen => write!(f, "')'"),
+            Token::OpenBracket => write!(f, "'['"),
+            Token::CloseBracket => write!(f, "']'"),
+            Token::Comma => write!(f, "','"),
+            Token::Operator(operator) => write!(f, "operator {:?}", operator),
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Value(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Parses a compact filter expression, e.g.
+/// `executions.status == FAILED and (tags contains critical or executions.durationMs >= 5000)`.
+///
+/// Values are JSON literals, lists in brackets or bare words, which are strings. `and` binds
+/// tighter than `or`.
+pub fn parse_filter_expression(expression: &str) -> Result<DataFilter, String> {
+    let tokens = tokenize(expression)?;
+    let mut parser = ExpressionParser {
+        tokens,
+        position: 0,
+    };
+    let node = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("Unexpected {} in filter expression", token));
+    }
+
+    Ok(match node {
+        FilterNode::Filter(filter) => filter,
+        condition => DataFilter {
+            operator: LogicalOperator::And,
+            conditions: vec![condition],
+        },
+    })
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = expression.trim_start();
+
+    while let Some(character) = rest.chars().next() {
+        let length = match character {
+            '(' | ')' | '[' | ']' | ',' => {
+                tokens.push(match character {
+                    '(' => Token::OpenParen,
+                    ')' => Token::CloseParen,
+                    '[' => Token::OpenBracket,
+                    ']' => Token::CloseBracket,
+                    _ => Token::Comma,
+                });
+                1
+            }
+            '=' | '!' | '<' | '>' => {
+                let (operator, length) = match rest.get(..2) {
+                    Some("==") => (ConditionOperator::Equals, 2),
+                    Some("!=") => (ConditionOperator::NotEquals, 2),
+                    Some(">=") => (ConditionOperator::GreaterOrEqual, 2),
+                    Some("<=") => (ConditionOperator::LessOrEqual, 2),
+                    _ if character == '>' => (ConditionOperator::Greater, 1),
+                    _ if character == '<' => (ConditionOperator::Less, 1),
+                    _ => return Err(format!("Unknown operator in filter expression: {}", rest)),
+                };
+                tokens.push(Token::Operator(operator));
+                length
+            }
+            '"' => {
+                let mut stream = serde_json::Deserializer::from_str(rest).into_iter::<Value>();
+                let value = stream
+                    .next()
+                    .ok_or_else(|| "Unterminated string in filter expression".to_string())?
+                    .map_err(|e| format!("Invalid string in filter expression: {}", e))?;
+                tokens.push(Token::Value(value));
+                stream.byte_offset()
+            }
+            _ => {
+                let length = rest
+                    .find(|c: char| c.is_whitespace() || "()[],=!<>\"".contains(c))
+                    .unwrap_or(rest.len());
+                tokens.push(Token::Word(rest[..length].to_string()));
+                length
+            }
+        };
+        rest = rest[length..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+struct ExpressionParser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl ExpressionParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| "Unexpected end of filter expression".to_string())?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn next_is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        let token = self.next()?;
+        if token != expected {
+            return Err(format!(
+                "Expected {} but found {} in filter expression",
+                expected, token
+            ));
+        }
+        Ok(())
+    }
+
+    fn parse_or(&mut self) -> Result<FilterNode, String> {
+        self.parse_logical(LogicalOperator::Or, "or", Self::parse_and)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterNode, String> {
+        self.parse_logical(LogicalOperator::And, "and", Self::parse_primary)
+    }
+
+    fn parse_logical(
+        &mut self,
+        operator: LogicalOperator,
+        keyword: &str,
+        parse_operand: fn(&mut Self) -> Result<FilterNode, String>,
+    ) -> Result<FilterNode, String> {
+        let mut conditions = vec![parse_operand(self)?];
+        while self.next_is_keyword(keyword) {
+            self.position += 1;
+            conditions.push(parse_operand(self)?);
+        }
+
+        if conditions.len() == 1 {
+            return Ok(conditions.remove(0));
+        }
+        Ok(FilterNode::Filter(DataFilter {
+            operator,
+            conditions,
+        }))
+    }
+
+    fn parse_primary(&mut self) -> Result<FilterNode, String> {
+        if self.peek() == Some(&Token::OpenParen) {
+            self.position += 1;
+            let node = self.parse_or()?;
+            self.expect(Token::CloseParen)?;
+            return Ok(node);
+        }
+
+        let field = match self.next()? {
+            Token::Word(field) => field,
+            token => return Err(format!("Expected a field but found {}", token)),
+        };
+        let operator = self.parse_operator()?;
+        let value = self.parse_value()?;
+
+        Ok(FilterNode::Condition(Condition {
+            field,
+            operator,
+            value,
+        }))
+    }
+
+    fn parse_operator(&mut self) -> Result<ConditionOperator, String> {
+        let operator = match self.next()? {
+            Token::Operator(operator) => operator,
+            Token::Word(word) => match word.to_ascii_lowercase().as_str() {
+                "in" => ConditionOperator::In,
+                "contains" => ConditionOperator::Contains,
+                "equals" => ConditionOperator::Equals,
+                "not" => {
+                    if self.next_is_keyword("in") {
+                        self.position += 1;
+                        ConditionOperator::NotIn
+                    } else if self.next_is_keyword("equals") {
+                        self.position += 1;
+                        ConditionOperator::NotEquals
+                    } else {
+                        return Err("Expected 'in' or 'equals' after 'not'".to_string());
+                    }
+                }
+                _ => return Err(format!("Unknown operator in filter expression: {}", word)),
+            },
+            token => return Err(format!("Expected an operator but found {}", token)),
+        };
+        Ok(operator)
+    }
+
+    fn parse_value(&mut self) -> Result<Value, String> {
+        match self.next()? {
+            Token::Value(value) => Ok(value),
+            Token::Word(word) => Ok(match word.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" => Value::Null,
+                _ => serde_json::from_str::<serde_json::Number>(&word)
+                    .map(Value::Number)
+                    .unwrap_or(Value::String(word)),
+            }),
+            Token::OpenBracket => {
+                let mut values = Vec::new();
+                if self.peek() == Some(&Token::CloseBracket) {
+                    self.position += 1;
+                    return Ok(Value::Array(values));
+                }
+                loop {
+                    values.push(self.parse_value()?);
+                    match self.next()? {
+                        Token::Comma => continue,
+                        Token::CloseBracket => return Ok(Value::Array(values)),
+                        token => {
+                            return Err(format!("Expected ',' or ']' but found {}", token));
+                        }
+                    }
+                }
+            }
+            token => Err(format!("Expected a value but found {}", token)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::create_execution;
+    use crate::test_report::{TestExecution, TestExecutionStatus};
+    use parameterized::parameterized;
+
+    fn create_test(
+        name: &str,
+        status: TestExecutionStatus,
+        duration_ms: u64,
+        tags: Option<Vec<&str>>,
+    ) -> TestReportTest {
+        TestReportTest {
+            id: name.to_string(),
+            name: name.to_string(),
+            path: format!("path/to/{}", name),
+            executions: vec![TestExecution {
+                duration_ms,
+                ..create_execution(status)
+            }],
+            tags: tags.map(|tags| tags.into_iter().map(|s| s.to_string()).collect()),
+            ..Default::default()
+        }
+    }
+
+    fn tests() -> Vec<TestReportTest> {
+        vec![
+            create_test(
+                "Test 1",
+                TestExecutionStatus::Passed,
+                100,
+                Some(vec!["fast", "smoke"]),
+            ),
+            create_test(
+                "Test 2",
+                TestExecutionStatus::Failed,
+                200,
+                Some(vec!["slow"]),
+            ),
+            create_test(
+                "Another Test",
+                TestExecutionStatus::Passed,
+                150,
+                Some(vec!["smoke", "regression"]),
+            ),
+            create_test("Skipped Test", TestExecutionStatus::Skipped, 50, None),
+        ]
+    }
+
+    fn matching_names(filter: &DataFilter) -> Vec<String> {
+        tests()
+            .into_iter()
+            .filter(|test| filter.matches(test))
+            .map(|test| test.name)
+            .collect()
+    }
+
+    #[parameterized(expression = {
+        "executions.status == PASSED",
+        "executions.status equals \"FAILED\"",
+        "executions.status != PASSED",
+        "executions.status not equals PASSED",
+        "executions.status in [FAILED, SKIPPED]",
+        "executions.status not in [FAILED, SKIPPED]",
+        "name contains Another",
+        "tags contains smoke",
+        "tags == [\"slow\"]",
+        "tags == null",
+        "executions.durationMs >= 150",
+        "executions.durationMs > 150",
+        "executions.durationMs < 100.5",
+        "executions.durationMs <= 50",
+        "tags contains smoke and executions.durationMs > 100",
+        "tags contains slow or executions.status == SKIPPED",
+        "(tags contains slow OR tags contains fast) AND executions.durationMs < 150",
+        "executions.status == PASSED or tags contains slow and executions.durationMs > 500",
+    }, expected = {
+        vec!["Test 1", "Another Test"],
+        vec!["Test 2"],
+        vec!["Test 2", "Skipped Test"],
+        vec!["Test 2", "Skipped Test"],
+        vec!["Test 2", "Skipped Test"],
+        vec!["Test 1", "Another Test"],
+        vec!["Another Test"],
+        vec!["Test 1", "Another Test"],
+        vec!["Test 2"],
+        vec!["Skipped Test"],
+        vec!["Test 2", "Another Test"],
+        vec!["Test 2"],
+        vec!["Test 1", "Skipped Test"],
+        vec!["Skipped Test"],
+        vec!["Another Test"],
+        vec!["Test 2", "Skipped Test"],
+        vec!["Test 1"],
+        vec!["Test 1", "Another Test"],
+    })]
+    fn expressions_filter_tests(expression: &str, expected: Vec<&str>) {
+        let filter = parse_filter_expression(expression).unwrap();
+
+        assert_eq!(matching_names(&filter), expected);
+    }
+
+    #[test]
+    fn json_filters_use_the_dashboard_format() {
+        let filter: DataFilter = serde_json::from_str(
+            r#"{
+                "operator": "AND",
+                "conditions": [
+                    {"field": "path", "operator": "contains", "value": "Test"},
+                    {
+                        "operator": "OR",
+                        "conditions": [
+                            {"field": "executions.status", "operator": "==", "value": "FAILED"},
+                            {"field": "tags", "operator": "contains", "value": "regression"}
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(matching_names(&filter), vec!["Test 2", "Another Test"]);
+    }
+
+    #[test]
+    fn execution_fields_are_those_of_the_latest_execution() {
+        let mut test = create_test("Retried", TestExecutionStatus::Failed, 100, None);
+        let failed = test.executions[0].clone();
+        test.executions = vec![
+            TestExecution {
+                timestamp: "2024-01-01T00:00:00.5Z".to_string(),
+                status: TestExecutionStatus::Passed,
+                ..failed.clone()
+            },
+            failed.clone(),
+            TestExecution {
+                timestamp: "2023-12-31T23:59:59.999Z".to_string(),
+                ..failed
+            },
+        ];
+
+        let passed = parse_filter_expression("executions.status == PASSED").unwrap();
+        let failed = parse_filter_expression("executions.status == FAILED").unwrap();
+
+        assert!(passed.matches(&test));
+        assert!(!failed.matches(&test));
+    }
+
+    #[test]
+    fn executions_sharing_a_timestamp_use_the_first_one_like_the_dashboard() {
+        let mut test = create_test("Retried", TestExecutionStatus::Failed, 100, None);
+        let failed = test.executions[0].clone();
+        test.executions = vec![
+            failed.clone(),
+            TestExecution {
+                status: TestExecutionStatus::Passed,
+                ..failed
+            },
+        ];
+
+        let failed = parse_filter_expression("executions.status == FAILED").unwrap();
+
+        assert!(failed.matches(&test));
+        assert_eq!(
+            test.latest_execution().unwrap().status,
+            TestExecutionStatus::Failed
+        );
+    }
+
+    #[test]
+    fn empty_filter_matches_every_test() {
+        let filter = DataFilter {
+            operator: LogicalOperator::Or,
+            conditions: Vec::new(),
+        };
+
+        assert_eq!(matching_names(&filter).len(), 4);
+    }
+
+    #[parameterized(expression = {
+        "",
+        "name",
+        "name = x",
+        "name == x and",
+        "(name == x",
+        "name == [a b]",
+        "name between x",
+        "name == \"x",
+    })]
+    fn invalid_expressions_are_errors(expression: &str) {
+        assert!(parse_filter_expression(expression).is_err());
+    }
+}
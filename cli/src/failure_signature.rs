@@ -0,0 +1,198 @@
+use crate::test_identity::{VOLATILE_PATTERNS, fnv1a_64};
+use regex::Regex;
+use std::sync::LazyLock;
+
+static VARIABLE_FRAGMENTS: LazyLock<Vec<(Regex, &str)>> = LazyLock::new(|| {
+    VOLATILE_PATTERNS
+        .into_iter()
+        .chain([
+            (r"(?:\b[A-Za-z]:)?(?:[\\/][\w.\-]+){2,}[\\/]?", "<path>"),
+            (r"(^|[^A-Za-z_])\d+(\.\d+)?", "${1}<n>"),
+        ])
+        .map(|(pattern, replacement)| (Regex::new(pattern).unwrap(), replacement))
+        .collect()
+});
+
+static PYTHON_FRAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^File "([^"]+)", line \d+, in (.+)$"#).unwrap());
+
+static JAVA_MODULE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[\w.]+//|^[\w.]+@[\w.\-]+/").unwrap());
+
+static JAVA_SOURCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s*\(([\w$.]+(:\d+)?|Native Method|Unknown Source)\)$").unwrap());
+
+static LINE_NUMBERS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(:\d+)+(\)?)$").unwrap());
+
+/// Packages of the runtime, test frameworks and build tools, which are never in the project.
+const LIBRARY_PACKAGES: &[&str] = &[
+    "java.",
+    "javax.",
+    "jdk.",
+    "sun.",
+    "kotlin.",
+    "scala.",
+    "org.junit.",
+    "org.opentest4j.",
+    "org.testng.",
+    "org.gradle.",
+    "org.apache.maven.",
+    "node:",
+];
+
+const LIBRARY_PATHS: &[&str] = &["node_modules", "site-packages", "/usr/lib/"];
+
+/// Groups failures with the same cause: the failure message without values that change
+/// between failures, and the top stack frame of the project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailureSignature {
+    pub message: String,
+    pub frame: Option<String>,
+}
+
+impl FailureSignature {
+    /// Frames starting with one of the project prefixes are in the project. Without prefixes,
+    /// every frame that is not part of a known library is.
+    pub fn new(
+        message: Option<&str>,
+        stack_trace: Option<&str>,
+        project_prefixes: &[String],
+    ) -> FailureSignature {
+        let message = message
+            .filter(|message| !message.trim().is_empty())
+            .or_else(|| stack_trace.and_then(|stack_trace| stack_trace.lines().next()))
+            .unwrap_or_default();
+
+        FailureSignature {
+            message: normalize_message(message),
+            frame: stack_trace.and_then(|stack_trace| top_frame(stack_trace, project_prefixes)),
+        }
+    }
+
+    pub fn id(&self) -> String {
+        let key = format!(
+            "{}\u{1f}{}",
+            self.message,
+            self.frame.as_deref().unwrap_or_default()
+        );
+        format!("{:016x}", fnv1a_64(key.as_bytes()))
+    }
+}
+
+/// Replaces numbers, UUIDs, paths, timestamps and memory addresses in a failure message.
+pub fn normalize_message(message: &str) -> String {
+    let first_line = message.trim().lines().next().unwrap_or_default();
+    VARIABLE_FRAGMENTS.iter().fold(
+        first_line.to_string(),
+        |normalized, (regex, replacement)| {
+            regex.replace_all(&normalized, *replacement).into_owned()
+        },
+    )
+}
+
+/// Returns the first frame of the stack trace that is in the project, without line numbers.
+pub fn top_frame(stack_trace: &str, project_prefixes: &[String]) -> Option<String> {
+    stack_trace
+        .lines()
+        .filter_map(|line| frame(line.trim()))
+        .find(|frame| {
+            if project_prefixes.is_empty() {
+                !LIBRARY_PACKAGES
+                    .iter()
+                    .any(|package| frame.starts_with(package))
+                    && !LIBRARY_PATHS.iter().any(|path| frame.contains(path))
+            } else {
+                project_prefixes
+                    .iter()
+                    .any(|prefix| frame.starts_with(prefix.as_str()))
+            }
+        })
+}
+
+/// Java frames become `package.Class.method`, JavaScript frames `function (file)` or `file`
+/// and Python frames `file in function`.
+fn frame(line: &str) -> Option<String> {
+    if let Some(frame) = line.strip_prefix("at ") {
+        let frame = JAVA_MODULE.replace(frame.trim(), "");
+        let frame = JAVA_SOURCE.replace(&frame, "");
+        return Some(LINE_NUMBERS.replace(&frame, "$2").into_owned());
+    }
+
+    PYTHON_FRAME
+        .captures(line)
+        .map(|captures| format!("{} in {}", &captures[1], &captures[2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parameterized::parameterized;
+
+    #[parameterized(message = {
+        "Expected 3 but was 42",
+        "Connection to 10.0.0.12:5432 refused",
+        "Order 123e4567-e89b-12d3-a456-426614174000 not found",
+        "Timed out at 2024-01-01T10:00:00.123Z",
+        "Object@1b6d3586 is closed",
+        "Cannot read /tmp/run-17/data/input.json",
+        "Cannot read C:\\builds\\17\\input.json",
+        "first line\nsecond line",
+    }, expected = {
+        "Expected <n> but was <n>",
+        "Connection to <n>.<n>:<n> refused",
+        "Order <uuid> not found",
+        "Timed out at <timestamp>",
+        "Object<address> is closed",
+        "Cannot read <path>",
+        "Cannot read <path>",
+        "first line",
+    })]
+    fn messages_are_normalized(message: &str, expected: &str) {
+        assert_eq!(normalize_message(message), expected);
+    }
+
+    #[parameterized(stack_trace = {
+        "AssertionFailedError\nat app//org.junit.jupiter.api.Assertions.fail(Assertions.java:115)\nat app//com.example.OrderTest.creates(OrderTest.java:10)",
+        "at java.base@21.0.3/java.lang.reflect.Method.invoke(Native Method)\nat com.example.Order.<init>(Order.java:5)",
+        "Error: boom\n    at Object.<anonymous> (/home/ci/project/node_modules/jest/index.js:1:2)\n    at run (/home/ci/project/src/order.test.js:10:5)",
+        "Error: boom\n    at /home/ci/project/src/order.js:3:7",
+        "Traceback (most recent call last):\n  File \"/usr/lib/python3/site-packages/pytest.py\", line 1, in main\n  File \"tests/test_order.py\", line 12, in test_create",
+    }, expected = {
+        "com.example.OrderTest.creates",
+        "com.example.Order.<init>",
+        "run (/home/ci/project/src/order.test.js)",
+        "/home/ci/project/src/order.js",
+        "tests/test_order.py in test_create",
+    })]
+    fn top_frame_skips_library_frames(stack_trace: &str, expected: &str) {
+        assert_eq!(top_frame(stack_trace, &[]).as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn top_frame_uses_project_prefixes() {
+        let stack_trace =
+            "at com.vendor.Client.send(Client.java:3)\nat com.example.Api.call(Api.java:7)";
+
+        let frame = top_frame(stack_trace, &["com.example.".to_string()]);
+
+        assert_eq!(frame.as_deref(), Some("com.example.Api.call"));
+    }
+
+    #[test]
+    fn signature_falls_back_to_first_line_of_stack_trace() {
+        let first = FailureSignature::new(
+            None,
+            Some("TimeoutError: waited 5000ms\nat com.example.Page.open(Page.java:1)"),
+            &[],
+        );
+        let second = FailureSignature::new(
+            Some(""),
+            Some("TimeoutError: waited 3000ms\nat com.example.Page.open(Page.java:9)"),
+            &[],
+        );
+
+        assert_eq!(first.message, "TimeoutError: waited <n>ms");
+        assert_eq!(first.frame.as_deref(), Some("com.example.Page.open"));
+        assert_eq!(first.id(), second.id());
+    }
+}
@@ -0,0 +1,367 @@
+use glob::{MatchOptions, Pattern};
+use std::fs;
+use std::path::Path;
+
+pub const STDIO_PATH: &str = "-";
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+pub struct InputFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    default_extensions: Vec<String>,
+}
+
+impl InputFilter {
+    pub fn new(
+        include: &[String],
+        exclude: &[String],
+        default_extensions: &[&str],
+    ) -> Result<InputFilter, String> {
+        Ok(InputFilter {
+            include: compile_patterns(include)?,
+            exclude: compile_patterns(exclude)?,
+            default_extensions: default_extensions.iter().map(|e| e.to_string()).collect(),
+        })
+    }
+
+    fn accepts_discovered(&self, path: &Path) -> bool {
+        if self.is_excluded(path) {
+            return false;
+        }
+
+        if !self.include.is_empty() {
+            return self.include.iter().any(|p| pattern_matches(p, path));
+        }
+
+        if self.default_extensions.is_empty() {
+            return true;
+        }
+
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                self.default_extensions
+                    .iter()
+                    .any(|e| e.eq_ignore_ascii_case(extension))
+            })
+    }
+
+    /// Files matched by a glob pattern must match the include patterns, if any, but not the
+    /// default extensions.
+    fn accepts_matched(&self, path: &Path) -> bool {
+        !self.is_excluded(path)
+            && (self.include.is_empty() || self.include.iter().any(|p| pattern_matches(p, path)))
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        self.exclude.iter().any(|p| pattern_matches(p, path))
+    }
+}
+
+pub fn resolve_input_paths(inputs: &[String], filter: &InputFilter) -> Vec<String> {
+    let mut file_paths: Vec<String> = Vec::new();
+
+    for input in inputs {
+        let path = Path::new(input);
+
+        if input == STDIO_PATH {
+            file_paths.push(input.clone());
+        } else if path.is_dir() {
+            collect_directory_files(path, filter, &mut file_paths);
+        } else if path.exists() || !is_glob_pattern(input) {
+            if !filter.is_excluded(path) {
+                file_paths.push(input.clone());
+            }
+        } else {
+            collect_glob_files(input, filter, &mut file_paths);
+        }
+    }
+
+    file_paths.sort();
+    file_paths.dedup();
+    file_paths
+}
+
+fn collect_directory_files(directory: &Path, filter: &InputFilter, file_paths: &mut Vec<String>) {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Error reading directory {}: {}", directory.display(), e);
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let entry_path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        if file_type.is_dir() {
+            collect_directory_files(&entry_path, filter, file_paths);
+        } else if entry_path.is_file()
+            && filter.accepts_discovered(&entry_path)
+            && let Some(path_str) = entry_path.to_str()
+        {
+            file_paths.push(path_str.to_string());
+        }
+    }
+}
+
+fn collect_glob_files(pattern: &str, filter: &InputFilter, file_paths: &mut Vec<String>) {
+    let entries = match glob::glob_with(pattern, MATCH_OPTIONS) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Invalid input pattern {}: {}", pattern, e);
+            return;
+        }
+    };
+
+    let mut matched_any = false;
+    for entry_path in entries.flatten() {
+        matched_any = true;
+        if entry_path.is_dir() {
+            collect_directory_files(&entry_path, filter, file_paths);
+        } else if filter.accepts_matched(&entry_path)
+            && let Some(path_str) = entry_path.to_str()
+        {
+            file_paths.push(path_str.to_string());
+        }
+    }
+
+    if !matched_any {
+        eprintln!("No files matched input pattern {}", pattern);
+    }
+}
+
+fn compile_patterns(patterns: &[String]) -> Result<Vec<Pattern>, String> {
+    patterns
+        .iter()
+        .map(|p| Pattern::new(p).map_err(|e| format!("Invalid pattern {}: {}", p, e)))
+        .collect()
+}
+
+fn pattern_matches(pattern: &Pattern, path: &Path) -> bool {
+    if pattern.as_str().contains('/') {
+        let normalized_path = path.to_string_lossy().replace('\\', "/");
+        let normalized_path = normalized_path.trim_start_matches("./");
+        return pattern.matches_with(normalized_path, MATCH_OPTIONS);
+    }
+
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| pattern.matches_with(name, MATCH_OPTIONS))
+}
+
+fn is_glob_pattern(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::{NamedTempFile, tempdir};
+
+    fn path_string(path: &Path) -> String {
+        path.to_str().unwrap().to_string()
+    }
+
+    fn create_files(root: &Path, relative_paths: &[&str]) {
+        for relative_path in relative_paths {
+            let file_path = root.join(relative_path);
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            File::create(file_path).unwrap();
+        }
+    }
+
+    fn no_filter() -> InputFilter {
+        InputFilter::new(&[], &[], &[]).unwrap()
+    }
+
+    #[test]
+    fn given_file_path_return_list_with_one_element() {
+        let file = NamedTempFile::new().unwrap();
+        let file_path = path_string(file.path());
+
+        let result = resolve_input_paths(std::slice::from_ref(&file_path), &no_filter());
+
+        assert_eq!(result, vec![file_path]);
+    }
+
+    #[test]
+    fn given_stdio_path_return_list_with_stdio_path() {
+        let result = resolve_input_paths(&[STDIO_PATH.to_string()], &no_filter());
+
+        assert_eq!(result, vec![STDIO_PATH.to_string()]);
+    }
+
+    #[test]
+    fn given_folder_return_files_from_all_levels_sorted() {
+        let dir = tempdir().unwrap();
+        create_files(dir.path(), &["b.xml", "a.xml", "module/nested/c.xml"]);
+
+        let result = resolve_input_paths(&[path_string(dir.path())], &no_filter());
+
+        assert_eq!(
+            result,
+            vec![
+                path_string(&dir.path().join("a.xml")),
+                path_string(&dir.path().join("b.xml")),
+                path_string(&dir.path().join("module").join("nested").join("c.xml")),
+            ]
+        );
+    }
+
+    #[test]
+    fn given_folder_with_default_extensions_skip_other_files() {
+        let dir = tempdir().unwrap();
+        create_files(
+            dir.path(),
+            &["a.xml", "b.XML", "notes.txt", "module/binary.bin"],
+        );
+        let filter = InputFilter::new(&[], &[], &["xml"]).unwrap();
+
+        let result = resolve_input_paths(&[path_string(dir.path())], &filter);
+
+        assert_eq!(
+            result,
+            vec![
+                path_string(&dir.path().join("a.xml")),
+                path_string(&dir.path().join("b.XML")),
+            ]
+        );
+    }
+
+    #[test]
+    fn given_explicit_file_with_other_extension_keep_it() {
+        let dir = tempdir().unwrap();
+        create_files(dir.path(), &["report.txt"]);
+        let file_path = path_string(&dir.path().join("report.txt"));
+        let filter = InputFilter::new(&[], &[], &["xml"]).unwrap();
+
+        let result = resolve_input_paths(std::slice::from_ref(&file_path), &filter);
+
+        assert_eq!(result, vec![file_path]);
+    }
+
+    #[test]
+    fn given_multiple_inputs_return_deduplicated_union() {
+        let dir = tempdir().unwrap();
+        create_files(dir.path(), &["one/a.xml", "two/b.xml"]);
+        let file_path = path_string(&dir.path().join("one").join("a.xml"));
+
+        let result = resolve_input_paths(
+            &[
+                path_string(&dir.path().join("two")),
+                path_string(&dir.path().join("one")),
+                file_path.clone(),
+            ],
+            &no_filter(),
+        );
+
+        assert_eq!(
+            result,
+            vec![
+                file_path,
+                path_string(&dir.path().join("two").join("b.xml"))
+            ]
+        );
+    }
+
+    #[test]
+    fn given_glob_pattern_return_matching_files_recursively() {
+        let dir = tempdir().unwrap();
+        create_files(
+            dir.path(),
+            &[
+                "module-a/build/test-results/test/TEST-a.xml",
+                "module-b/build/test-results/test/TEST-b.xml",
+                "module-b/build/test-results/test/other.xml",
+            ],
+        );
+        let pattern = format!("{}/**/TEST-*.xml", dir.path().to_str().unwrap());
+
+        let result = resolve_input_paths(&[pattern], &no_filter());
+
+        assert_eq!(result.len(), 2);
+        assert!(result[0].ends_with("TEST-a.xml"));
+        assert!(result[1].ends_with("TEST-b.xml"));
+    }
+
+    #[test]
+    fn given_glob_pattern_without_matches_return_empty_list() {
+        let dir = tempdir().unwrap();
+        let pattern = format!("{}/**/*.xml", dir.path().to_str().unwrap());
+
+        let result = resolve_input_paths(&[pattern], &no_filter());
+
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn include_patterns_select_discovered_files() {
+        let dir = tempdir().unwrap();
+        create_files(
+            dir.path(),
+            &["TEST-a.xml", "nested/TEST-b.xml", "nested/c.xml"],
+        );
+        let filter = InputFilter::new(&["TEST-*.xml".to_string()], &[], &["xml"]).unwrap();
+
+        let result = resolve_input_paths(&[path_string(dir.path())], &filter);
+
+        assert_eq!(
+            result,
+            vec![
+                path_string(&dir.path().join("TEST-a.xml")),
+                path_string(&dir.path().join("nested").join("TEST-b.xml")),
+            ]
+        );
+    }
+
+    #[test]
+    fn include_patterns_select_files_matched_by_a_glob_pattern() {
+        let dir = tempdir().unwrap();
+        create_files(dir.path(), &["TEST-a.xml", "nested/TEST-b.xml", "c.xml"]);
+        let pattern = format!("{}/**/*.xml", dir.path().to_str().unwrap());
+        let filter = InputFilter::new(&["TEST-*.xml".to_string()], &[], &["xml"]).unwrap();
+
+        let result = resolve_input_paths(&[pattern], &filter);
+
+        assert_eq!(
+            result,
+            vec![
+                path_string(&dir.path().join("TEST-a.xml")),
+                path_string(&dir.path().join("nested").join("TEST-b.xml")),
+            ]
+        );
+    }
+
+    #[test]
+    fn exclude_patterns_remove_files_matched_by_name_or_path() {
+        let dir = tempdir().unwrap();
+        create_files(dir.path(), &["a.xml", "b.xml", "integration/c.xml"]);
+        let filter = InputFilter::new(
+            &[],
+            &["b.xml".to_string(), "**/integration/*".to_string()],
+            &[],
+        )
+        .unwrap();
+
+        let result = resolve_input_paths(&[path_string(dir.path())], &filter);
+
+        assert_eq!(result, vec![path_string(&dir.path().join("a.xml"))]);
+    }
+
+    #[test]
+    fn invalid_pattern_returns_error() {
+        let result = InputFilter::new(&["[".to_string()], &[], &[]);
+
+        assert!(result.is_err());
+    }
+}
@@ -1,5 +1,23 @@
-﻿pub mod commands;
+﻿pub mod attachments;
+pub mod commands;
+pub mod data_filter;
+pub mod failure_signature;
+pub mod input_paths;
+pub mod output_format;
+pub mod parser_registry;
 pub mod parsers;
+pub mod report_io;
+pub mod report_migrations;
+pub mod report_schema;
+pub mod report_stats;
+pub mod run_metadata;
+pub mod tag_matcher;
+pub mod tag_rules;
+#[cfg(test)]
+pub(crate) mod test_fixtures;
+pub mod test_identity;
 pub mod test_models;
+pub mod test_parameters;
 pub mod test_parser;
 pub mod test_report;
+pub mod timestamps;
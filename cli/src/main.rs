@@ -1,7 +1,25 @@
-use chrono;
-use clap::Parser as ClapParser;
-use cli::commands::parse_command::parse_command;
-use cli::commands::tag_command::tag_command;
+use chrono::{SubsecRound, Utc};
+use clap::error::ErrorKind;
+use clap::{ArgGroup, Args as ClapArgs, CommandFactory, Parser as ClapParser};
+use cli::commands::clusters_command::{ClustersOptions, clusters_command};
+use cli::commands::diff_command::{DiffCategory, DiffOptions, diff_command};
+use cli::commands::flaky_command::{FlakyOptions, flaky_command};
+use cli::commands::gate_command::{GateOptions, gate_command};
+use cli::commands::list_formats_command::list_formats_command;
+use cli::commands::merge_command::{Retention, merge_command};
+use cli::commands::parse_command::{ParseOptions, parse_command};
+use cli::commands::perf_command::{PerfOptions, perf_command};
+use cli::commands::query_command::{QueryFormat, QueryOptions, query_command};
+use cli::commands::schema_command::schema_command;
+use cli::commands::split_command::{SplitBy, SplitOptions, split_command};
+use cli::commands::summary_command::{SummaryOptions, summary_command};
+use cli::commands::tag_command::{parse_tag_expressions, tag_command};
+use cli::commands::validate_command::validate_command;
+use cli::data_filter::{DataFilter, parse_filter_expression, read_filter};
+use cli::output_format::OutputFormat;
+use cli::run_metadata::RunMetadata;
+use cli::tag_rules::{TagRule, read_tag_rules};
+use cli::timestamps::{SourceTimezone, format_timestamp, normalize_timestamp};
 
 #[derive(ClapParser, Debug)]
 #[command(version, about, long_about = None)]
@@ -11,19 +29,91 @@ struct Args {
 }
 
 #[derive(ClapParser, Debug)]
+#[allow(clippy::large_enum_variant)]
 enum Commands {
     Parse {
-        #[arg(short, long, help = "Type of the test report (e.g., junit)")]
+        #[arg(
+            short,
+            long,
+            help = "Type of the test report (e.g., junit or auto), or the name or path of a parser plugin"
+        )]
         report_type: String,
 
-        #[arg(short, long, help = "Input file or directory path")]
-        input: String,
+        #[arg(
+            short,
+            long,
+            required = true,
+            num_args = 1..,
+            help = "Input files, directories (searched recursively) or glob patterns, or '-' to read from stdin"
+        )]
+        input: Vec<String>,
 
-        #[arg(short, long, help = "Output file path")]
+        #[arg(
+            long,
+            help = "Only parse files from folders and glob inputs matching these glob patterns"
+        )]
+        include: Vec<String>,
+
+        #[arg(long, help = "Skip files matching these glob patterns")]
+        exclude: Vec<String>,
+
+        #[arg(short, long, help = "Output file path, or '-' to write to stdout")]
         output: Option<String>,
 
         #[arg(long, help = "Tags to add to all tests")]
         tag: Vec<String>,
+
+        #[arg(long, help = "TOML file with the tag rules to apply to the tests")]
+        rules: Option<String>,
+
+        #[arg(
+            short,
+            long,
+            default_value_t = 0,
+            help = "Number of files to parse in parallel (0 uses all available cores)"
+        )]
+        jobs: usize,
+
+        #[arg(
+            long,
+            help = "Copy the attachments of the tests into this directory, relative to the directory of the output file"
+        )]
+        attachments_dir: Option<String>,
+
+        #[arg(
+            long,
+            default_value_t = 0,
+            help = "Inline attachments up to this size in bytes as base64 (0 disables inlining)"
+        )]
+        inline_attachments_max_bytes: u64,
+
+        #[arg(
+            long,
+            default_value = "UTC",
+            help = "Time zone of source timestamps without one: UTC, local, an offset such as +02:00 or a name such as Europe/Berlin"
+        )]
+        timezone: SourceTimezone,
+
+        #[arg(
+            long,
+            help = "Timestamp of the report, used instead of the current time for reproducible builds"
+        )]
+        timestamp: Option<String>,
+
+        #[arg(
+            long,
+            help = "Set the parent of parameterized test invocations so they can be grouped"
+        )]
+        group_parameterized: bool,
+
+        #[arg(
+            long,
+            help = "Write an empty report instead of failing when no input file is found"
+        )]
+        allow_empty: bool,
+
+        #[command(flatten)]
+        run: RunArgs,
     },
     Tag {
         #[arg(short, long, help = "Input file path")]
@@ -32,9 +122,435 @@ enum Commands {
         #[arg(short, long, help = "Output file path")]
         output: Option<String>,
 
-        #[arg(long, help = "Tag to add in the format 'expression:operation:tag1,tag2' where operation can be add, remove, or update")]
+        #[arg(
+            long,
+            help = "Tag to add in the format 'expression:operation:tag1,tag2' where expression is a tag matcher (e.g., 'duration > 5s') or a path regex, and operation can be add, remove, or update"
+        )]
         tag: Vec<String>,
+
+        #[arg(
+            long,
+            help = "TOML file with the tag rules to apply before the --tag expressions"
+        )]
+        rules: Option<String>,
+    },
+    Merge {
+        #[arg(long, help = "History report file path, created if it does not exist")]
+        history: String,
+
+        #[arg(
+            short,
+            long,
+            required = true,
+            num_args = 1..,
+            help = "Report files to add to the history"
+        )]
+        input: Vec<String>,
+
+        #[arg(
+            short,
+            long,
+            help = "Output file path (defaults to overwriting the history file)"
+        )]
+        output: Option<String>,
+
+        #[arg(
+            long,
+            help = "Keep only the last N runs, and the executions of each test in its last N runs"
+        )]
+        max_runs: Option<usize>,
+
+        #[arg(
+            long,
+            help = "Drop executions older than N days before the newest report"
+        )]
+        max_age_days: Option<u64>,
+    },
+    #[command(about = "Compare a report against a base report, e.g. of the main branch")]
+    Diff {
+        #[arg(long, help = "Base report file path")]
+        base: String,
+
+        #[arg(long, help = "Report file path to compare against the base report")]
+        head: String,
+
+        #[arg(short, long, help = "Output file path (defaults to stdout)")]
+        output: Option<String>,
+
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, help = "Output format")]
+        format: OutputFormat,
+
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            default_value = "newly-failing",
+            help = "Exit with code 1 if any test falls into these categories"
+        )]
+        fail_on: Vec<DiffCategory>,
+
+        #[arg(
+            long,
+            default_value_t = 50.0,
+            help = "Minimum increase of the duration in percent for a test to be slower"
+        )]
+        slower_threshold_percent: f64,
+
+        #[arg(
+            long,
+            default_value_t = 100,
+            help = "Minimum increase of the duration in milliseconds for a test to be slower"
+        )]
+        slower_min_ms: u64,
+    },
+    #[command(
+        about = "Check a report against quality rules and exit with an error if any is violated",
+        group(ArgGroup::new("rules").required(true).multiple(true))
+    )]
+    Gate {
+        #[arg(short, long, help = "Input file path")]
+        input: String,
+
+        #[arg(
+            long,
+            group = "rules",
+            help = "Maximum number of failed tests and errored suites"
+        )]
+        max_failures: Option<u64>,
+
+        #[arg(
+            long,
+            group = "rules",
+            help = "Minimum percentage of the tests that ran that must pass"
+        )]
+        min_pass_rate: Option<f64>,
+
+        #[arg(long, group = "rules", help = "Tests with this tag must not fail")]
+        no_failures_tagged: Vec<String>,
+
+        #[arg(
+            long,
+            group = "rules",
+            help = "Maximum total duration of the tests in milliseconds"
+        )]
+        max_duration_ms: Option<u64>,
+
+        #[arg(
+            long,
+            group = "rules",
+            help = "Report whose passing or skipped tests must not fail, e.g. of the main branch"
+        )]
+        baseline: Option<String>,
     },
+    #[command(about = "Print statistics of the latest execution of every test in a report")]
+    Summary {
+        #[arg(short, long, help = "Input file path, or '-' to read from stdin")]
+        input: String,
+
+        #[arg(short, long, help = "Output file path (defaults to stdout)")]
+        output: Option<String>,
+
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, help = "Output format")]
+        format: OutputFormat,
+
+        #[arg(long, default_value_t = 10, help = "Number of slowest tests to list")]
+        top: usize,
+
+        #[arg(long, help = "Print the statistics of every tag")]
+        group_by_tag: bool,
+    },
+    #[command(about = "Find tests that flip between passing and failing in a history report")]
+    Flaky {
+        #[arg(
+            short,
+            long,
+            help = "Input file path, e.g. a history report created by merge"
+        )]
+        input: String,
+
+        #[arg(short, long, help = "Output file path (defaults to stdout)")]
+        output: Option<String>,
+
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, help = "Output format")]
+        format: OutputFormat,
+
+        #[arg(
+            long,
+            default_value_t = 20,
+            help = "Number of most recent executions of each test to analyse"
+        )]
+        window: usize,
+
+        #[arg(
+            long,
+            default_value_t = 0.1,
+            help = "Minimum share of consecutive executions that changed between passing and failing"
+        )]
+        min_flip_rate: f64,
+
+        #[arg(long, help = "Tag to add to the flaky tests of the input report")]
+        tag: Option<String>,
+
+        #[arg(
+            long,
+            requires = "tag",
+            help = "Path of the tagged report (defaults to overwriting the input file)"
+        )]
+        tagged_output: Option<String>,
+    },
+    #[command(about = "Group failed tests by normalized failure message and stack frame")]
+    Clusters {
+        #[arg(short, long, help = "Input file path")]
+        input: String,
+
+        #[arg(short, long, help = "Output file path (defaults to stdout)")]
+        output: Option<String>,
+
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, help = "Output format")]
+        format: OutputFormat,
+
+        #[arg(
+            long,
+            default_value_t = 5,
+            help = "Number of example tests to list per cluster"
+        )]
+        examples: usize,
+
+        #[arg(
+            long,
+            help = "Prefix of the stack frames of the project (e.g., com.example.), instead of skipping known library frames"
+        )]
+        project_prefix: Vec<String>,
+
+        #[arg(
+            long,
+            help = "Set the cluster id of every failed execution of the input report"
+        )]
+        stamp: bool,
+
+        #[arg(
+            long,
+            requires = "stamp",
+            help = "Path of the stamped report (defaults to overwriting the input file)"
+        )]
+        stamped_output: Option<String>,
+    },
+    #[command(about = "Find tests whose duration regressed in a history report")]
+    Perf {
+        #[arg(
+            short,
+            long,
+            help = "Input file path, e.g. a history report created by merge"
+        )]
+        input: String,
+
+        #[arg(short, long, help = "Output file path (defaults to stdout)")]
+        output: Option<String>,
+
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, help = "Output format")]
+        format: OutputFormat,
+
+        #[arg(
+            long,
+            default_value_t = 10,
+            help = "Number of worst regressions to list"
+        )]
+        top: usize,
+
+        #[arg(
+            long,
+            default_value_t = 10,
+            help = "Number of previous normal executions the baseline and the fixed reference are computed from"
+        )]
+        window: usize,
+
+        #[arg(
+            long,
+            default_value_t = 5,
+            help = "Minimum number of normal executions before comparing executions to the baseline"
+        )]
+        min_baseline: usize,
+
+        #[arg(
+            long,
+            default_value_t = 3,
+            help = "Number of most recent executions that must all be slow"
+        )]
+        recent: usize,
+
+        #[arg(
+            long,
+            default_value_t = 3.5,
+            help = "Minimum distance from the baseline median in scaled median absolute deviations"
+        )]
+        threshold: f64,
+
+        #[arg(
+            long,
+            default_value_t = 100,
+            help = "Minimum increase over the baseline median in milliseconds"
+        )]
+        min_increase_ms: u64,
+
+        #[arg(
+            long,
+            default_value_t = 20.0,
+            help = "Minimum increase over the baseline median in percent"
+        )]
+        min_increase_percent: f64,
+    },
+    #[command(
+        about = "Select the tests of a report that match a filter",
+        group(ArgGroup::new("query_filter").required(true))
+    )]
+    Query {
+        #[arg(short, long, help = "Input file path, or '-' to read from stdin")]
+        input: String,
+
+        #[arg(short, long, help = "Output file path (defaults to stdout)")]
+        output: Option<String>,
+
+        #[arg(
+            long = "where",
+            group = "query_filter",
+            value_parser = parse_filter_expression,
+            help = "Filter expression, e.g. 'executions.status == FAILED and tags contains critical'"
+        )]
+        filter_expression: Option<DataFilter>,
+
+        #[arg(
+            long,
+            group = "query_filter",
+            help = "JSON file with a filter in the format of the dashboard widget filters"
+        )]
+        filter: Option<String>,
+
+        #[arg(long, value_enum, default_value_t = QueryFormat::Report, help = "Output format")]
+        format: QueryFormat,
+    },
+    #[command(about = "Split a report into one report per tag, path prefix or status")]
+    Split {
+        #[arg(short, long, help = "Input file path, or '-' to read from stdin")]
+        input: String,
+
+        #[arg(
+            short,
+            long,
+            help = "Directory to write the reports and their manifest.json to"
+        )]
+        output_dir: String,
+
+        #[arg(long, value_enum, default_value_t = SplitBy::Tag, help = "How to partition the tests")]
+        by: SplitBy,
+
+        #[arg(
+            long,
+            default_value = "*",
+            help = "Only split by tags matching this glob pattern (e.g., 'owner:*')"
+        )]
+        tag_pattern: String,
+
+        #[arg(
+            long,
+            default_value_t = 1,
+            help = "Number of path segments of the path prefixes"
+        )]
+        depth: usize,
+
+        #[arg(
+            long,
+            help = "Separator of the path segments (defaults to / if a path contains one, otherwise .)"
+        )]
+        path_separator: Option<String>,
+    },
+    #[command(about = "List the supported report types and the parser plugins found on PATH")]
+    ListFormats,
+    #[command(about = "Print the JSON Schema of the report format")]
+    Schema {
+        #[arg(short, long, help = "Output file path (defaults to stdout)")]
+        output: Option<String>,
+    },
+    #[command(about = "Check that report files match the JSON Schema of the report format")]
+    Validate {
+        #[arg(
+            short,
+            long,
+            required = true,
+            num_args = 1..,
+            help = "Report files to validate, or '-' to read from stdin"
+        )]
+        input: Vec<String>,
+    },
+}
+
+#[derive(ClapArgs, Debug)]
+struct RunArgs {
+    #[arg(
+        long,
+        help = "Identifier of the run (detected from CI variables by default)"
+    )]
+    run_id: Option<String>,
+
+    #[arg(
+        long,
+        help = "URL of the CI job (detected from CI variables by default)"
+    )]
+    ci_job_url: Option<String>,
+
+    #[arg(long, help = "Commit SHA (detected from CI variables by default)")]
+    commit: Option<String>,
+
+    #[arg(long, help = "Branch name (detected from CI variables by default)")]
+    branch: Option<String>,
+
+    #[arg(long, help = "Environment the tests ran in (e.g., staging)")]
+    environment: Option<String>,
+
+    #[arg(long, help = "Host name of the machine the tests ran on")]
+    hostname: Option<String>,
+
+    #[arg(
+        long,
+        help = "Version of the tool that produced the report (e.g., gradle-8.5)"
+    )]
+    format_version: Option<String>,
+}
+
+impl RunArgs {
+    fn into_run_metadata(self) -> RunMetadata {
+        RunMetadata {
+            id: self.run_id,
+            ci_job_url: self.ci_job_url,
+            commit_sha: self.commit,
+            branch: self.branch,
+            environment: self.environment,
+            hostname: self.hostname,
+            format_version: self.format_version,
+        }
+        .or(RunMetadata::from_environment())
+    }
+}
+
+fn report_timestamp(timestamp: Option<String>, timezone: SourceTimezone) -> String {
+    match timestamp {
+        Some(timestamp) => normalize_timestamp(&timestamp, timezone).unwrap_or_else(|| {
+            Args::command()
+                .error(
+                    ErrorKind::InvalidValue,
+                    format!("Invalid timestamp: {}", timestamp),
+                )
+                .exit()
+        }),
+        None => format_timestamp(Utc::now().trunc_subsecs(3)),
+    }
+}
+
+fn read_rules(path: Option<String>) -> Vec<TagRule> {
+    path.map(|path| {
+        read_tag_rules(&path)
+            .unwrap_or_else(|e| Args::command().error(ErrorKind::InvalidValue, e).exit())
+    })
+    .unwrap_or_default()
 }
 
 fn main() {
@@ -44,17 +560,218 @@ fn main() {
         Commands::Parse {
             report_type,
             input,
+            include,
+            exclude,
             output,
             tag,
-        } => parse_command(
-            report_type,
+            rules,
+            jobs,
+            attachments_dir,
+            inline_attachments_max_bytes,
+            group_parameterized,
+            allow_empty,
+            timezone,
+            timestamp,
+            run,
+        } => {
+            if !parse_command(ParseOptions {
+                report_type,
+                inputs: input,
+                include,
+                exclude,
+                output: output.unwrap_or("output.json".to_string()),
+                current_date: report_timestamp(timestamp, timezone),
+                tags: tag,
+                tag_rules: read_rules(rules),
+                jobs,
+                run: run.into_run_metadata(),
+                attachments_dir,
+                inline_attachments_max_bytes,
+                group_parameterized,
+                allow_empty,
+                timezone,
+            }) {
+                std::process::exit(1);
+            }
+        }
+        Commands::Tag {
             input,
-            output.unwrap_or("output.json".to_string()),
-            chrono::Utc::now().to_string(),
+            output,
             tag,
+            rules,
+        } => {
+            let mut tag_rules = read_rules(rules);
+            tag_rules.extend(
+                parse_tag_expressions(tag)
+                    .unwrap_or_else(|e| Args::command().error(ErrorKind::InvalidValue, e).exit()),
+            );
+            tag_command(input, output, tag_rules);
+        }
+        Commands::Merge {
+            history,
+            input,
+            output,
+            max_runs,
+            max_age_days,
+        } => merge_command(
+            history,
+            input,
+            output,
+            Retention {
+                max_runs,
+                max_age_days,
+            },
         ),
-        Commands::Tag { input, output, tag } => {
-            tag_command(input, output, tag);
+        Commands::Diff {
+            base,
+            head,
+            output,
+            format,
+            fail_on,
+            slower_threshold_percent,
+            slower_min_ms,
+        } => {
+            if !diff_command(DiffOptions {
+                base,
+                head,
+                output,
+                format,
+                fail_on,
+                slower_threshold_percent,
+                slower_min_ms,
+            }) {
+                std::process::exit(1);
+            }
+        }
+        Commands::Gate {
+            input,
+            max_failures,
+            min_pass_rate,
+            no_failures_tagged,
+            max_duration_ms,
+            baseline,
+        } => {
+            if !gate_command(GateOptions {
+                input,
+                max_failures,
+                min_pass_rate,
+                no_failures_tagged,
+                max_duration_ms,
+                baseline,
+            }) {
+                std::process::exit(1);
+            }
+        }
+        Commands::Summary {
+            input,
+            output,
+            format,
+            top,
+            group_by_tag,
+        } => summary_command(SummaryOptions {
+            input,
+            output,
+            format,
+            top,
+            group_by_tag,
+        }),
+        Commands::Flaky {
+            input,
+            output,
+            format,
+            window,
+            min_flip_rate,
+            tag,
+            tagged_output,
+        } => flaky_command(FlakyOptions {
+            input,
+            output,
+            format,
+            window,
+            min_flip_rate,
+            tag,
+            tagged_output,
+        }),
+        Commands::Clusters {
+            input,
+            output,
+            format,
+            examples,
+            project_prefix,
+            stamp,
+            stamped_output,
+        } => clusters_command(ClustersOptions {
+            input,
+            output,
+            format,
+            examples,
+            project_prefixes: project_prefix,
+            stamp,
+            stamped_output,
+        }),
+        Commands::Perf {
+            input,
+            output,
+            format,
+            top,
+            window,
+            min_baseline,
+            recent,
+            threshold,
+            min_increase_ms,
+            min_increase_percent,
+        } => perf_command(PerfOptions {
+            input,
+            output,
+            format,
+            top,
+            window,
+            min_baseline,
+            recent,
+            threshold,
+            min_increase_ms,
+            min_increase_percent,
+        }),
+        Commands::Query {
+            input,
+            output,
+            filter_expression,
+            filter,
+            format,
+        } => {
+            let filter = filter_expression.unwrap_or_else(|| {
+                let path = filter.expect("Either a filter expression or file is required");
+                read_filter(&path)
+                    .unwrap_or_else(|e| Args::command().error(ErrorKind::InvalidValue, e).exit())
+            });
+            query_command(QueryOptions {
+                input,
+                output,
+                filter,
+                format,
+            })
+        }
+        Commands::Split {
+            input,
+            output_dir,
+            by,
+            tag_pattern,
+            depth,
+            path_separator,
+        } => split_command(SplitOptions {
+            input,
+            output_dir,
+            by,
+            tag_pattern,
+            depth,
+            path_separator,
+        }),
+        Commands::ListFormats => list_formats_command(),
+        Commands::Schema { output } => schema_command(output),
+        Commands::Validate { input } => {
+            if !validate_command(input) {
+                std::process::exit(1);
+            }
         }
     }
 }
@@ -0,0 +1,24 @@
+use clap::ValueEnum;
+
+/// Format of the output of commands that report on a test report.
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Markdown,
+}
+
+/// Escapes a value so it can be written in a Markdown table cell.
+pub fn escape_markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Names a test by its path and name, e.g. `com.example.OrderTest > createsOrder()`.
+pub fn test_label(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} > {}", path, name)
+    }
+}
@@ -0,0 +1,267 @@
+use crate::parsers::external::ExternalParser;
+use crate::parsers::external::parser::PLUGIN_PREFIX;
+use crate::parsers::junit::JunitParser;
+use crate::test_models::TestSuite;
+use crate::test_parser::TestParser;
+use std::io::Read;
+use std::sync::Arc;
+
+pub const AUTO_REPORT_TYPE: &str = "auto";
+
+const DETECTION_PREFIX_LENGTH: usize = 4096;
+
+pub struct ParserRegistration {
+    pub name: String,
+    pub description: String,
+    pub file_extensions: Vec<String>,
+    pub detect: fn(&[u8]) -> bool,
+    pub parser: Arc<dyn TestParser>,
+}
+
+#[derive(Default)]
+pub struct ParserRegistry {
+    registrations: Vec<ParserRegistration>,
+}
+
+impl ParserRegistry {
+    pub fn new() -> ParserRegistry {
+        ParserRegistry::default()
+    }
+
+    pub fn with_builtin_parsers() -> ParserRegistry {
+        let mut registry = ParserRegistry::new();
+        registry.register(ParserRegistration {
+            name: "junit".to_string(),
+            description: "JUnit XML reports (Maven, Gradle, Jest, pytest and most other tools)"
+                .to_string(),
+            file_extensions: vec!["xml".to_string()],
+            detect: detect_junit,
+            parser: Arc::new(JunitParser),
+        });
+        registry
+    }
+
+    /// Adds a parser, replacing any registration with the same name.
+    pub fn register(&mut self, registration: ParserRegistration) {
+        match self
+            .registrations
+            .iter_mut()
+            .find(|existing| existing.name == registration.name)
+        {
+            Some(existing) => *existing = registration,
+            None => self.registrations.push(registration),
+        }
+    }
+
+    pub fn registrations(&self) -> &[ParserRegistration] {
+        &self.registrations
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ParserRegistration> {
+        self.registrations
+            .iter()
+            .find(|registration| registration.name == name)
+    }
+
+    pub fn detect(&self, content: &[u8]) -> Option<&ParserRegistration> {
+        let prefix = &content[..content.len().min(DETECTION_PREFIX_LENGTH)];
+        self.registrations
+            .iter()
+            .find(|registration| (registration.detect)(prefix))
+    }
+
+    /// Resolves a report type to a registered parser, automatic detection or a parser plugin.
+    pub fn resolve(&self, report_type: &str) -> Result<Arc<dyn TestParser + '_>, String> {
+        if report_type == AUTO_REPORT_TYPE {
+            return Ok(Arc::new(DetectingParser { registry: self }));
+        }
+
+        if let Some(registration) = self.get(report_type) {
+            return Ok(registration.parser.clone());
+        }
+
+        match ExternalParser::find(report_type) {
+            Some(parser) => Ok(Arc::new(parser)),
+            None => Err(format!(
+                "Unknown report_type: {}. Supported types: {}, or a parser plugin named {}{} on PATH",
+                report_type,
+                self.supported_types().join(", "),
+                PLUGIN_PREFIX,
+                report_type
+            )),
+        }
+    }
+
+    pub fn file_extensions(&self, report_type: &str) -> Vec<&str> {
+        let registrations: Vec<&ParserRegistration> = if report_type == AUTO_REPORT_TYPE {
+            self.registrations.iter().collect()
+        } else {
+            self.get(report_type).into_iter().collect()
+        };
+
+        let mut extensions: Vec<&str> = registrations
+            .iter()
+            .flat_map(|registration| registration.file_extensions.iter().map(String::as_str))
+            .collect();
+        extensions.sort();
+        extensions.dedup();
+        extensions
+    }
+
+    fn supported_types(&self) -> Vec<&str> {
+        self.registrations
+            .iter()
+            .map(|registration| registration.name.as_str())
+            .chain([AUTO_REPORT_TYPE])
+            .collect()
+    }
+}
+
+struct DetectingParser<'a> {
+    registry: &'a ParserRegistry,
+}
+
+impl TestParser for DetectingParser<'_> {
+    fn parse_reader(&self, reader: &mut dyn Read) -> Result<Vec<TestSuite>, String> {
+        let mut content = Vec::new();
+        reader
+            .read_to_end(&mut content)
+            .map_err(|e| format!("I/O error: {}", e))?;
+
+        match self.registry.detect(&content) {
+            Some(registration) => registration.parser.parse_bytes(&content),
+            None => Err("Could not detect the report type".to_string()),
+        }
+    }
+}
+
+fn detect_junit(content: &[u8]) -> bool {
+    let content = String::from_utf8_lossy(content);
+    content.contains("<testsuites") || content.contains("<testsuite")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_models::{Test, TestStatus};
+
+    struct FixedParser;
+
+    impl TestParser for FixedParser {
+        fn parse_reader(&self, _reader: &mut dyn Read) -> Result<Vec<TestSuite>, String> {
+            Ok(vec![TestSuite {
+                name: "fixed".to_string(),
+                duration: 0.0,
+                timestamp: String::new(),
+                tests: vec![Test {
+                    name: "test".to_string(),
+                    identity: None,
+                    time: 0.0,
+                    status: TestStatus::Passed,
+                    attachments: Vec::new(),
+                    start_time: None,
+                    parameters: Default::default(),
+                    parent: None,
+                    stack_trace: None,
+                }],
+                ..Default::default()
+            }])
+        }
+    }
+
+    fn fixed_registration(name: &str) -> ParserRegistration {
+        ParserRegistration {
+            name: name.to_string(),
+            description: "Fixed format".to_string(),
+            file_extensions: vec!["fixed".to_string(), "xml".to_string()],
+            detect: |content| content.starts_with(b"FIXED"),
+            parser: Arc::new(FixedParser),
+        }
+    }
+
+    #[test]
+    fn builtin_registry_contains_junit() {
+        let registry = ParserRegistry::with_builtin_parsers();
+
+        let registration = registry.get("junit").unwrap();
+
+        assert_eq!(registration.file_extensions, vec!["xml".to_string()]);
+        assert!(!registration.description.is_empty());
+    }
+
+    #[test]
+    fn resolve_unknown_report_type_returns_error() {
+        let registry = ParserRegistry::with_builtin_parsers();
+
+        let result = registry.resolve("unsupported");
+
+        let error = result.err().unwrap();
+        assert!(error.contains("Unknown report_type: unsupported"));
+        assert!(error.contains("junit, auto"));
+    }
+
+    #[test]
+    fn registered_parser_can_be_resolved() {
+        let mut registry = ParserRegistry::with_builtin_parsers();
+        registry.register(fixed_registration("fixed"));
+
+        let suites = registry.resolve("fixed").unwrap().parse_bytes(b"").unwrap();
+
+        assert_eq!(suites[0].name, "fixed");
+    }
+
+    #[test]
+    fn register_replaces_parser_with_same_name() {
+        let mut registry = ParserRegistry::with_builtin_parsers();
+        registry.register(fixed_registration("junit"));
+
+        let suites = registry.resolve("junit").unwrap().parse_bytes(b"").unwrap();
+
+        assert_eq!(registry.registrations().len(), 1);
+        assert_eq!(suites[0].name, "fixed");
+    }
+
+    #[test]
+    fn detect_returns_matching_registration() {
+        let mut registry = ParserRegistry::with_builtin_parsers();
+        registry.register(fixed_registration("fixed"));
+
+        let junit = registry.detect(br#"<?xml version="1.0"?><testsuites></testsuites>"#);
+        let fixed = registry.detect(b"FIXED report");
+        let unknown = registry.detect(b"{}");
+
+        assert_eq!(junit.unwrap().name, "junit");
+        assert_eq!(fixed.unwrap().name, "fixed");
+        assert!(unknown.is_none());
+    }
+
+    #[test]
+    fn auto_report_type_parses_with_detected_parser() {
+        let mut registry = ParserRegistry::with_builtin_parsers();
+        registry.register(fixed_registration("fixed"));
+        let parser = registry.resolve(AUTO_REPORT_TYPE).unwrap();
+
+        let junit_suites = parser
+            .parse_bytes(br#"<testsuite name="junit_suite"></testsuite>"#)
+            .unwrap();
+        let fixed_suites = parser.parse_bytes(b"FIXED").unwrap();
+        let unknown = parser.parse_bytes(b"unknown");
+
+        assert_eq!(junit_suites[0].name, "junit_suite");
+        assert_eq!(fixed_suites[0].name, "fixed");
+        assert_eq!(unknown.unwrap_err(), "Could not detect the report type");
+    }
+
+    #[test]
+    fn file_extensions_for_auto_report_type_are_combined() {
+        let mut registry = ParserRegistry::with_builtin_parsers();
+        registry.register(fixed_registration("fixed"));
+
+        assert_eq!(registry.file_extensions("junit"), vec!["xml"]);
+        assert_eq!(
+            registry.file_extensions(AUTO_REPORT_TYPE),
+            vec!["fixed", "xml"]
+        );
+        assert!(registry.file_extensions("plugin").is_empty());
+    }
+}
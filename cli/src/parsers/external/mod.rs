@@ -0,0 +1,4 @@
+mod models;
+pub mod parser;
+
+pub use parser::ExternalParser;
@@ -0,0 +1,63 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExternalTestSuite {
+    pub name: String,
+    #[serde(default)]
+    pub duration: f64,
+    #[serde(default)]
+    pub timestamp: String,
+    #[serde(default)]
+    pub hostname: Option<String>,
+    #[serde(default)]
+    pub errors: Vec<String>,
+    #[serde(default)]
+    pub tests: Vec<ExternalTest>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExternalTest {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub time: f64,
+    pub status: ExternalTestStatus,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<ExternalAttachment>,
+    #[serde(default)]
+    pub parameters: BTreeMap<String, String>,
+    #[serde(default)]
+    pub parent: Option<String>,
+    #[serde(rename = "startTime", default)]
+    pub start_time: Option<String>,
+    #[serde(rename = "stackTrace", default)]
+    pub stack_trace: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExternalAttachment {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub path: String,
+    #[serde(rename = "mimeType", default)]
+    pub mime_type: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub enum ExternalTestStatus {
+    #[serde(rename = "PASSED")]
+    Passed,
+    #[serde(rename = "SKIPPED")]
+    Skipped,
+    #[serde(rename = "FAILED")]
+    Failed,
+    #[serde(rename = "ERROR")]
+    Error,
+}
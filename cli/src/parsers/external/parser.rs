@@ -0,0 +1,476 @@
+use crate::parsers::external::models::{ExternalTest, ExternalTestStatus, ExternalTestSuite};
+use crate::test_models::{Test, TestAttachment, TestStatus, TestSuite};
+use crate::test_parser::TestParser;
+use std::ffi::OsStr;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::{env, thread};
+
+pub const PLUGIN_PREFIX: &str = "test-prism-parser-";
+
+const STDIN_ARGUMENT: &str = "-";
+
+/// Runs an external executable that converts a report into normalized test suites.
+///
+/// The executable receives a single argument, the path of the report or `-` when the report
+/// is written to its standard input, and must print a JSON array of suites on standard output.
+pub struct ExternalParser {
+    executable: PathBuf,
+}
+
+impl TestParser for ExternalParser {
+    fn parse_reader(&self, reader: &mut dyn Read) -> Result<Vec<TestSuite>, String> {
+        let mut content = Vec::new();
+        reader
+            .read_to_end(&mut content)
+            .map_err(|e| format!("I/O error: {}", e))?;
+
+        let mut child = self
+            .command(OsStr::new(STDIN_ARGUMENT))
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| self.spawn_error(e))?;
+
+        let mut stdin = child.stdin.take().expect("Plugin stdin is piped");
+        let output = thread::scope(|scope| {
+            scope.spawn(move || {
+                // The plugin may exit without reading everything, which is reported through its status.
+                let _ = stdin.write_all(&content);
+            });
+            child.wait_with_output()
+        })
+        .map_err(|e| format!("I/O error: {}", e))?;
+
+        self.read_output(output)
+    }
+
+    fn parse(&self, file_path: &Path) -> Result<Vec<TestSuite>, String> {
+        let output = self
+            .command(file_path.as_os_str())
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| self.spawn_error(e))?;
+
+        self.read_output(output)
+    }
+}
+
+impl ExternalParser {
+    pub fn new(executable: PathBuf) -> ExternalParser {
+        ExternalParser { executable }
+    }
+
+    /// Resolves a report type to a plugin, either an explicit executable path or
+    /// `test-prism-parser-<report_type>` on `PATH`.
+    pub fn find(report_type: &str) -> Option<ExternalParser> {
+        let explicit_path = Path::new(report_type);
+        if report_type.contains(['/', '\\']) {
+            return explicit_path
+                .is_file()
+                .then(|| ExternalParser::new(explicit_path.to_path_buf()));
+        }
+
+        let path_variable = env::var_os("PATH")?;
+        find_in_path(&format!("{}{}", PLUGIN_PREFIX, report_type), &path_variable)
+            .map(ExternalParser::new)
+    }
+
+    fn command(&self, argument: &OsStr) -> Command {
+        let mut command = Command::new(&self.executable);
+        command
+            .arg(argument)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        command
+    }
+
+    fn spawn_error(&self, error: std::io::Error) -> String {
+        format!(
+            "Failed to run parser plugin {}: {}",
+            self.executable.display(),
+            error
+        )
+    }
+
+    fn read_output(&self, output: Output) -> Result<Vec<TestSuite>, String> {
+        if !output.status.success() {
+            return Err(format!(
+                "Parser plugin {} exited with {}: {}",
+                self.executable.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        let suites: Vec<ExternalTestSuite> = serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Invalid parser plugin output: {}", e))?;
+        Self::validate_suites(&suites)?;
+
+        Ok(suites
+            .into_iter()
+            .map(Self::convert_to_test_suite)
+            .collect())
+    }
+
+    fn validate_suites(suites: &[ExternalTestSuite]) -> Result<(), String> {
+        for (suite_index, suite) in suites.iter().enumerate() {
+            validate_duration(suite.duration, || format!("[{}].duration", suite_index))?;
+
+            for (test_index, test) in suite.tests.iter().enumerate() {
+                if test.name.is_empty() {
+                    return Err(format!(
+                        "Invalid parser plugin output: [{}].tests[{}].name must not be empty",
+                        suite_index, test_index
+                    ));
+                }
+                for (attachment_index, attachment) in test.attachments.iter().enumerate() {
+                    if attachment.path.is_empty() {
+                        return Err(format!(
+                            "Invalid parser plugin output: [{}].tests[{}].attachments[{}].path must not be empty",
+                            suite_index, test_index, attachment_index
+                        ));
+                    }
+                }
+                validate_duration(test.time, || {
+                    format!("[{}].tests[{}].time", suite_index, test_index)
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn convert_to_test_suite(suite: ExternalTestSuite) -> TestSuite {
+        TestSuite {
+            name: suite.name,
+            duration: suite.duration,
+            timestamp: suite.timestamp,
+            hostname: suite.hostname,
+            declared_counts: None,
+            errors: suite.errors,
+            tests: suite.tests.into_iter().map(Self::convert_to_test).collect(),
+        }
+    }
+
+    fn convert_to_test(test: ExternalTest) -> Test {
+        let message = test.message.unwrap_or_default();
+        let status = match test.status {
+            ExternalTestStatus::Passed => TestStatus::Passed,
+            ExternalTestStatus::Skipped => TestStatus::Skipped(message),
+            ExternalTestStatus::Failed => TestStatus::Failed(message),
+            ExternalTestStatus::Error => TestStatus::Error(message),
+        };
+
+        Test {
+            identity: test.id,
+            name: test.name,
+            time: test.time,
+            status,
+            attachments: test
+                .attachments
+                .into_iter()
+                .map(|attachment| TestAttachment {
+                    name: attachment.name,
+                    path: attachment.path,
+                    mime_type: attachment.mime_type,
+                })
+                .collect(),
+            parameters: test.parameters,
+            parent: test.parent,
+            start_time: test.start_time,
+            stack_trace: test
+                .stack_trace
+                .filter(|stack_trace| !stack_trace.is_empty()),
+        }
+    }
+}
+
+fn validate_duration(value: f64, field: impl Fn() -> String) -> Result<(), String> {
+    if value.is_finite() && value >= 0.0 {
+        return Ok(());
+    }
+
+    Err(format!(
+        "Invalid parser plugin output: {} must be a non-negative number",
+        field()
+    ))
+}
+
+pub fn find_in_path(executable_name: &str, path_variable: &OsStr) -> Option<PathBuf> {
+    env::split_paths(path_variable).find_map(|directory| {
+        executable_candidates(executable_name)
+            .into_iter()
+            .map(|candidate| directory.join(candidate))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// Lists the `test-prism-parser-*` executables on `PATH` as (report type, executable) pairs.
+pub fn discover_plugins(path_variable: &OsStr) -> Vec<(String, PathBuf)> {
+    let mut plugins: Vec<(String, PathBuf)> = Vec::new();
+
+    for directory in env::split_paths(path_variable) {
+        let Ok(entries) = directory.read_dir() else {
+            continue;
+        };
+
+        for entry_path in entries.flatten().map(|entry| entry.path()) {
+            let Some(report_type) = plugin_report_type(&entry_path) else {
+                continue;
+            };
+            if entry_path.is_file() && !plugins.iter().any(|(name, _)| *name == report_type) {
+                plugins.push((report_type, entry_path));
+            }
+        }
+    }
+
+    plugins.sort();
+    plugins
+}
+
+fn plugin_report_type(executable_path: &Path) -> Option<String> {
+    let file_name = if cfg!(windows) {
+        executable_path.file_stem()?.to_str()?
+    } else {
+        executable_path.file_name()?.to_str()?
+    };
+
+    file_name
+        .strip_prefix(PLUGIN_PREFIX)
+        .filter(|report_type| !report_type.is_empty())
+        .map(str::to_string)
+}
+
+fn executable_candidates(executable_name: &str) -> Vec<String> {
+    if cfg!(windows) {
+        ["exe", "cmd", "bat"]
+            .iter()
+            .map(|extension| format!("{}.{}", executable_name, extension))
+            .collect()
+    } else {
+        vec![executable_name.to_string()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::{TempDir, tempdir};
+
+    #[cfg(unix)]
+    fn create_plugin(directory: &Path, name: &str, script: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let plugin_path = directory.join(name);
+        fs::write(&plugin_path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+        fs::set_permissions(&plugin_path, fs::Permissions::from_mode(0o755)).unwrap();
+        plugin_path
+    }
+
+    #[cfg(unix)]
+    fn create_plugin_with_output(output: &str) -> (TempDir, ExternalParser) {
+        let dir = tempdir().unwrap();
+        let script = format!("cat > /dev/null\ncat <<'JSON'\n{}\nJSON", output);
+        let plugin_path = create_plugin(dir.path(), "test-prism-parser-custom", &script);
+        (dir, ExternalParser::new(plugin_path))
+    }
+
+    #[test]
+    fn find_in_path_returns_executable_from_path_directories() {
+        let first_dir = tempdir().unwrap();
+        let second_dir = tempdir().unwrap();
+        let executable_name = executable_candidates("test-prism-parser-custom").remove(0);
+        let executable_path = second_dir.path().join(&executable_name);
+        fs::write(&executable_path, "").unwrap();
+        let path_variable =
+            env::join_paths([first_dir.path(), second_dir.path()]).expect("Valid PATH");
+
+        let result = find_in_path("test-prism-parser-custom", &path_variable);
+
+        assert_eq!(result, Some(executable_path));
+    }
+
+    #[test]
+    fn find_in_path_returns_none_when_executable_is_missing() {
+        let dir = tempdir().unwrap();
+        let path_variable = env::join_paths([dir.path()]).expect("Valid PATH");
+
+        let result = find_in_path("test-prism-parser-missing", &path_variable);
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn discover_plugins_lists_plugins_once_in_path_order() {
+        let first_dir = tempdir().unwrap();
+        let second_dir = tempdir().unwrap();
+        let foo_name = executable_candidates("test-prism-parser-foo").remove(0);
+        let bar_name = executable_candidates("test-prism-parser-bar").remove(0);
+        fs::write(first_dir.path().join(&foo_name), "").unwrap();
+        fs::write(second_dir.path().join(&foo_name), "").unwrap();
+        fs::write(second_dir.path().join(&bar_name), "").unwrap();
+        fs::write(second_dir.path().join("unrelated-tool"), "").unwrap();
+        let path_variable =
+            env::join_paths([first_dir.path(), second_dir.path()]).expect("Valid PATH");
+
+        let result = discover_plugins(&path_variable);
+
+        assert_eq!(
+            result,
+            vec![
+                ("bar".to_string(), second_dir.path().join(&bar_name)),
+                ("foo".to_string(), first_dir.path().join(&foo_name)),
+            ]
+        );
+    }
+
+    #[test]
+    fn find_with_missing_explicit_path_returns_none() {
+        let result = ExternalParser::find("./missing/test-prism-parser-custom");
+
+        assert!(result.is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parse_reader_converts_plugin_output() {
+        let (_dir, parser) = create_plugin_with_output(
+            r#"[
+                {
+                    "name": "suite",
+                    "duration": 1.5,
+                    "timestamp": "2024-01-01T00:00:00Z",
+                    "tests": [
+                        { "name": "passed", "time": 0.5, "status": "PASSED" },
+                        { "name": "failed", "time": 1.0, "status": "FAILED", "message": "boom",
+                          "attachments": [{ "name": "screenshot", "path": "shots/failed.png", "mimeType": "image/png" }] }
+                    ]
+                }
+            ]"#,
+        );
+
+        let result = parser.parse_bytes(b"proprietary report");
+
+        let suites = result.unwrap();
+        assert_eq!(suites.len(), 1);
+        assert_eq!(suites[0].name, "suite");
+        assert_eq!(suites[0].duration, 1.5);
+        assert_eq!(suites[0].timestamp, "2024-01-01T00:00:00Z");
+        assert_eq!(suites[0].tests[0].name, "passed");
+        assert_eq!(suites[0].tests[0].status, TestStatus::Passed);
+        assert_eq!(suites[0].tests[1].time, 1.0);
+        assert_eq!(
+            suites[0].tests[1].status,
+            TestStatus::Failed("boom".to_string())
+        );
+        assert_eq!(
+            suites[0].tests[1].attachments,
+            vec![TestAttachment {
+                name: Some("screenshot".to_string()),
+                path: "shots/failed.png".to_string(),
+                mime_type: Some("image/png".to_string()),
+            }]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parse_passes_the_report_path_to_the_plugin() {
+        let dir = tempdir().unwrap();
+        let report_path = dir.path().join("report.custom");
+        fs::write(&report_path, "suite-from-file").unwrap();
+        let plugin_path = create_plugin(
+            dir.path(),
+            "test-prism-parser-custom",
+            r#"printf '[{"name": "%s", "tests": []}]' "$(cat "$1")""#,
+        );
+
+        let result = ExternalParser::new(plugin_path).parse(&report_path);
+
+        let suites = result.unwrap();
+        assert_eq!(suites[0].name, "suite-from-file");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parse_reader_reports_plugin_failure() {
+        let dir = tempdir().unwrap();
+        let plugin_path = create_plugin(
+            dir.path(),
+            "test-prism-parser-custom",
+            "echo 'unsupported report' >&2\nexit 3",
+        );
+
+        let result = ExternalParser::new(plugin_path).parse_bytes(b"report");
+
+        let error = result.unwrap_err();
+        assert!(error.contains("exited with"));
+        assert!(error.contains("unsupported report"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parse_reader_rejects_output_that_is_not_json() {
+        let (_dir, parser) = create_plugin_with_output("not json");
+
+        let result = parser.parse_bytes(b"report");
+
+        assert!(result.unwrap_err().contains("Invalid parser plugin output"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parse_reader_rejects_unknown_status() {
+        let (_dir, parser) = create_plugin_with_output(
+            r#"[{ "name": "suite", "tests": [{ "name": "test", "status": "BROKEN" }] }]"#,
+        );
+
+        let result = parser.parse_bytes(b"report");
+
+        assert!(result.unwrap_err().contains("unknown variant"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parse_reader_rejects_unknown_fields() {
+        let (_dir, parser) = create_plugin_with_output(
+            r#"[{ "name": "suite", "tests": [{ "name": "test", "status": "PASSED", "owner": "me" }] }]"#,
+        );
+
+        let result = parser.parse_bytes(b"report");
+
+        assert!(result.unwrap_err().contains("unknown field"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parse_reader_rejects_negative_time() {
+        let (_dir, parser) = create_plugin_with_output(
+            r#"[{ "name": "suite", "tests": [{ "name": "test", "status": "PASSED", "time": -1 }] }]"#,
+        );
+
+        let result = parser.parse_bytes(b"report");
+
+        assert_eq!(
+            result.unwrap_err(),
+            "Invalid parser plugin output: [0].tests[0].time must be a non-negative number"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn parse_reader_rejects_empty_test_name() {
+        let (_dir, parser) = create_plugin_with_output(
+            r#"[{ "name": "suite", "tests": [{ "name": "", "status": "PASSED" }] }]"#,
+        );
+
+        let result = parser.parse_bytes(b"report");
+
+        assert_eq!(
+            result.unwrap_err(),
+            "Invalid parser plugin output: [0].tests[0].name must not be empty"
+        );
+    }
+}
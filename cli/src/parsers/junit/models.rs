@@ -21,17 +21,24 @@ pub struct JunitTestSuite {
     #[serde(rename = "@name")]
     pub name: String,
     #[serde(rename = "@tests")]
-    pub tests: u64,
+    pub tests: Option<u64>,
     #[serde(rename = "@failures")]
-    pub failures: u64,
+    pub failures: Option<u64>,
     #[serde(rename = "@errors")]
-    pub errors: u64,
+    pub errors: Option<u64>,
     #[serde(rename = "@skipped")]
-    pub skipped: u64,
+    pub skipped: Option<u64>,
     #[serde(rename = "@time")]
     pub time: f64,
     #[serde(rename = "@timestamp")]
     pub timestamp: String,
+    #[serde(rename = "@hostname")]
+    pub hostname: Option<String>,
+
+    #[serde(rename = "error", default)]
+    pub suite_errors: Vec<JunitError>,
+    #[serde(rename = "failure", default)]
+    pub suite_failures: Vec<JunitFailure>,
 
     #[serde(rename = "testcase", default)]
     pub test_cases: Vec<JunitTestCase>,
@@ -46,12 +53,16 @@ pub struct JunitTestCase {
     pub classname: String,
     #[serde(rename = "@time")]
     pub time: f64,
+    #[serde(rename = "@timestamp")]
+    pub timestamp: Option<String>,
     #[serde(rename = "failure", default)]
     pub failure: Option<JunitFailure>,
     #[serde(rename = "error", default)]
     pub error: Option<JunitError>,
     #[serde(rename = "skipped", default)]
     pub skipped: Option<JunitSkipped>,
+    #[serde(rename = "system-out", default)]
+    pub system_out: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Default, Deserialize)]
@@ -59,6 +70,8 @@ pub struct JunitTestCase {
 pub struct JunitFailure {
     #[serde(rename = "@message")]
     pub message: String,
+    #[serde(rename = "$text")]
+    pub stack_trace: String,
 }
 
 #[derive(Debug, PartialEq, Default, Deserialize)]
@@ -66,6 +79,8 @@ pub struct JunitFailure {
 pub struct JunitError {
     #[serde(rename = "@message")]
     pub message: String,
+    #[serde(rename = "$text")]
+    pub stack_trace: String,
 }
 
 #[derive(Debug, PartialEq, Default, Deserialize)]
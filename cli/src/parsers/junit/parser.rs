@@ -1,22 +1,32 @@
 ﻿use crate::parsers::junit::models::{JunitRoot, JunitTestCase, JunitTestSuite};
-use crate::test_models::{Test, TestStatus, TestSuite};
+use crate::test_models::{Test, TestAttachment, TestStatus, TestSuite};
+use crate::test_parameters::{ParameterizedName, parse_parameterized_name};
 use crate::test_parser::TestParser;
+use crate::test_report::TestCounts;
 use quick_xml::de::from_str;
-use std::fs;
-use std::path::Path;
+use regex::Regex;
+use std::io::Read;
+use std::sync::LazyLock;
+
+/// Attachment marker of the Jenkins JUnit attachments plugin, written to `system-out`.
+static ATTACHMENT_MARKER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[\[ATTACHMENT\|([^\]\r\n]+)\]\]").unwrap());
 
 pub struct JunitParser;
 
 impl TestParser for JunitParser {
-    fn parse(&self, file_path: &Path) -> Result<Vec<TestSuite>, String> {
-        let suites = Self::deserialize_suites(file_path)?;
+    fn parse_reader(&self, reader: &mut dyn Read) -> Result<Vec<TestSuite>, String> {
+        let suites = Self::deserialize_suites(reader)?;
         Ok(Self::convert_to_test_suites(suites))
     }
 }
 
 impl JunitParser {
-    fn deserialize_suites(file_path: &Path) -> Result<Vec<JunitTestSuite>, String> {
-        let content = fs::read_to_string(file_path).map_err(|e| format!("I/O error: {}", e))?;
+    fn deserialize_suites(reader: &mut dyn Read) -> Result<Vec<JunitTestSuite>, String> {
+        let mut content = String::new();
+        reader
+            .read_to_string(&mut content)
+            .map_err(|e| format!("I/O error: {}", e))?;
         let root: JunitRoot = from_str(&content).map_err(|e| e.to_string())?;
 
         match root {
@@ -29,44 +39,144 @@ impl JunitParser {
         suites
             .into_iter()
             .map(|junit_suite| {
+                let declared_counts = Self::declared_counts(&junit_suite);
+                let errors = Self::suite_errors(&junit_suite);
                 let tests = junit_suite
                     .test_cases
                     .into_iter()
-                    .map(|case| Self::convert_to_test(case))
+                    .map(Self::convert_to_test)
                     .collect();
 
                 TestSuite {
                     name: junit_suite.name,
                     duration: junit_suite.time,
                     timestamp: junit_suite.timestamp,
+                    hostname: junit_suite.hostname.filter(|hostname| !hostname.is_empty()),
+                    declared_counts,
+                    errors,
                     tests,
                 }
             })
             .collect()
     }
 
+    fn declared_counts(suite: &JunitTestSuite) -> Option<TestCounts> {
+        Some(TestCounts {
+            tests: suite.tests?,
+            failures: suite.failures.unwrap_or(0),
+            errors: suite.errors.unwrap_or(0),
+            skipped: suite.skipped.unwrap_or(0),
+        })
+    }
+
+    /// Some tools report failures of fixtures such as `@BeforeAll` directly on the suite.
+    fn suite_errors(suite: &JunitTestSuite) -> Vec<String> {
+        let error_messages = suite.suite_errors.iter().map(|error| &error.message);
+        let failure_messages = suite.suite_failures.iter().map(|failure| &failure.message);
+
+        error_messages
+            .chain(failure_messages)
+            .map(|message| {
+                if message.is_empty() {
+                    "Suite failed outside of its test cases".to_string()
+                } else {
+                    message.clone()
+                }
+            })
+            .collect()
+    }
+
     fn convert_to_test(case: JunitTestCase) -> Test {
-        let status = if let Some(failure) = case.failure {
-            TestStatus::Failed(failure.message)
+        let (status, stack_trace) = if let Some(failure) = case.failure {
+            (
+                TestStatus::Failed(failure.message),
+                Self::stack_trace(&failure.stack_trace),
+            )
         } else if let Some(error) = case.error {
-            TestStatus::Error(error.message)
+            (
+                TestStatus::Error(error.message),
+                Self::stack_trace(&error.stack_trace),
+            )
         } else if let Some(skipped) = case.skipped {
-            TestStatus::Skipped(skipped.message)
+            (TestStatus::Skipped(skipped.message), None)
         } else {
-            TestStatus::Passed
+            (TestStatus::Passed, None)
         };
+        let parameterized_name = parse_parameterized_name(&case.name)
+            .map(|name| ParameterizedName {
+                parent: Self::parent(&case.classname, name.parent),
+                ..name
+            })
+            .unwrap_or_default();
         Test {
+            identity: Some(Self::identity(&case.classname, &case.name)),
+            parameters: parameterized_name.parameters,
+            parent: parameterized_name.parent,
+            start_time: case.timestamp.filter(|timestamp| !timestamp.is_empty()),
+            stack_trace,
+            attachments: Self::attachments(case.system_out.as_deref().unwrap_or_default()),
             name: case.name,
             time: case.time,
             status,
         }
     }
+
+    /// Invocations belong to `classname#method`, like their ids. Gradle names JUnit 5
+    /// invocations `[index] display name`, without their method, so they belong to their class.
+    fn parent(classname: &str, method: Option<String>) -> Option<String> {
+        match method {
+            Some(method) if !classname.is_empty() => Some(format!("{}#{}", classname, method)),
+            Some(method) => Some(method),
+            None => Some(classname.to_string()).filter(|classname| !classname.is_empty()),
+        }
+    }
+
+    /// Removes the indentation of the XML document from the lines of a failure body.
+    fn stack_trace(body: &str) -> Option<String> {
+        let lines: Vec<&str> = body
+            .lines()
+            .map(str::trim_end)
+            .skip_while(|line| line.is_empty())
+            .collect();
+        let indentation = lines
+            .iter()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()?;
+
+        let stack_trace = lines
+            .iter()
+            .map(|line| line.get(indentation..).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(stack_trace.trim_end().to_string())
+    }
+
+    fn attachments(system_out: &str) -> Vec<TestAttachment> {
+        ATTACHMENT_MARKER
+            .captures_iter(system_out)
+            .map(|captures| TestAttachment {
+                name: None,
+                path: captures[1].trim().to_string(),
+                mime_type: None,
+            })
+            .collect()
+    }
+
+    /// JUnit 5 names parameterized invocations `[index] display name` without the method, so
+    /// the whole display name is kept to tell apart the invocations of different methods.
+    /// Volatile argument values are normalized when the id is derived.
+    fn identity(classname: &str, name: &str) -> String {
+        format!("{}#{}", classname, name)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_identity::test_id;
     use parameterized::parameterized;
+    use std::collections::HashSet;
     use std::io::Write;
     use std::path::PathBuf;
     use tempfile::NamedTempFile;
@@ -89,6 +199,182 @@ mod tests {
         assert!(result.unwrap_err().contains("I/O error:"));
     }
 
+    #[parameterized(classname = {
+        "com.example.MyClass",
+        "com.example.MyClass",
+        "com.example.MyClass",
+    }, name = {
+        "test_success",
+        "[2] two",
+        "[12] 2024-01-01T10:00:00Z",
+    }, expected = {
+        "com.example.MyClass#test_success",
+        "com.example.MyClass#[2] two",
+        "com.example.MyClass#[12] 2024-01-01T10:00:00Z",
+    })]
+    fn identity_uses_classname_and_method(classname: &str, name: &str, expected: &str) {
+        assert_eq!(JunitParser::identity(classname, name), expected);
+    }
+
+    #[test]
+    fn parameterized_methods_of_one_class_have_distinct_ids() {
+        let xml_content = r#"
+            <testsuite name="MyTestSuite" tests="3" failures="0" errors="0" skipped="0" time="0.3">
+                <testcase name="[1] a=2" classname="com.example.MyClass" time="0.1"/>
+                <testcase name="[1] x" classname="com.example.MyClass" time="0.1"/>
+                <testcase name="[2] x" classname="com.example.MyClass" time="0.1"/>
+            </testsuite>
+        "#;
+
+        let suites = JunitParser.parse_bytes(xml_content.as_bytes()).unwrap();
+        let ids: HashSet<String> = suites[0]
+            .tests
+            .iter()
+            .map(|test| test_id(&suites[0].name, test.identity.as_deref().unwrap()))
+            .collect();
+
+        assert_eq!(ids.len(), 3);
+    }
+
+    #[test]
+    fn invocations_belong_to_their_method_or_class() {
+        let xml_content = r#"
+            <testsuite name="MyTestSuite" tests="2" failures="0" errors="0" skipped="0" time="0.2">
+                <testcase name="[1] one" classname="com.example.MyClass" time="0.1"/>
+                <testcase name="testAdd[0: 1, 2]" classname="com.example.MyClass" time="0.1"/>
+                <testcase name="testAdd[1: 3, 4]" classname="com.example.MyClass" time="0.1"/>
+                <testcase name="testSub[0: 1, 2]" classname="com.example.MyClass" time="0.1"/>
+                <testcase name="Add(1,2)" classname="MyTests" time="0.1"/>
+            </testsuite>
+        "#;
+
+        let suites = JunitParser.parse_bytes(xml_content.as_bytes()).unwrap();
+
+        let parents: Vec<Option<&str>> = suites[0]
+            .tests
+            .iter()
+            .map(|test| test.parent.as_deref())
+            .collect();
+        assert_eq!(
+            parents,
+            vec![
+                Some("com.example.MyClass"),
+                Some("com.example.MyClass#testAdd"),
+                Some("com.example.MyClass#testAdd"),
+                Some("com.example.MyClass#testSub"),
+                Some("MyTests#Add"),
+            ]
+        );
+    }
+
+    #[test]
+    fn volatile_arguments_do_not_change_the_id() {
+        let first = JunitParser::identity("com.example.MyClass", "[1] 2024-01-01T10:00:00Z");
+        let second = JunitParser::identity("com.example.MyClass", "[1] 2024-02-03T11:12:13Z");
+
+        assert_eq!(test_id("suite", &first), test_id("suite", &second));
+    }
+
+    #[test]
+    fn parse_bytes_without_file() {
+        let xml_content = r#"
+            <testsuite name="MyTestSuite" tests="1" failures="0" errors="0" skipped="0" time="0.05" timestamp="2023-10-27T10:00:00Z">
+                <testcase name="test_success" classname="com.example.MyClass" time="0.05"/>
+            </testsuite>
+        "#;
+
+        let parser = JunitParser;
+        let result = parser.parse_bytes(xml_content.as_bytes());
+
+        assert!(result.is_ok());
+        let tests = result.unwrap();
+        assert_eq!(tests.len(), 1);
+        assert_eq!(tests[0].name, "MyTestSuite");
+        assert_eq!(tests[0].tests.len(), 1);
+        assert_eq!(tests[0].tests[0].name, "test_success");
+    }
+
+    #[test]
+    fn parse_reader_with_invalid_utf8_expect_error() {
+        let content: &[u8] = &[0x3c, 0xff, 0xfe];
+
+        let parser = JunitParser;
+        let result = parser.parse_reader(&mut &content[..]);
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("I/O error:"));
+    }
+
+    #[test]
+    fn parse_suite_keeps_declared_counts_and_suite_errors() {
+        let xml_content = r#"
+            <testsuite name="MyTestSuite" tests="2" failures="0" errors="1" hostname="build-agent" time="0.5">
+                <error message="Exception in @BeforeAll"/>
+            </testsuite>
+        "#;
+
+        let parser = JunitParser;
+        let suites = parser.parse_bytes(xml_content.as_bytes()).unwrap();
+
+        assert_eq!(suites.len(), 1);
+        assert!(suites[0].tests.is_empty());
+        assert_eq!(suites[0].hostname, Some("build-agent".to_string()));
+        assert_eq!(suites[0].errors, vec!["Exception in @BeforeAll"]);
+        assert_eq!(
+            suites[0].declared_counts,
+            Some(TestCounts {
+                tests: 2,
+                failures: 0,
+                errors: 1,
+                skipped: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_test_case_attachments_from_system_out() {
+        let xml_content = r#"
+            <testsuite name="MyTestSuite" tests="1">
+                <testcase name="test_login" classname="com.example.LoginTest" time="0.05">
+                    <failure message="timeout"/>
+                    <system-out><![CDATA[opening page
+[[ATTACHMENT|screenshots/login.png]]
+[[ATTACHMENT|/tmp/traces/login.zip]]
+]]></system-out>
+                </testcase>
+            </testsuite>
+        "#;
+
+        let parser = JunitParser;
+        let suites = parser.parse_bytes(xml_content.as_bytes()).unwrap();
+
+        let paths: Vec<&str> = suites[0].tests[0]
+            .attachments
+            .iter()
+            .map(|attachment| attachment.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            vec!["screenshots/login.png", "/tmp/traces/login.zip"]
+        );
+    }
+
+    #[test]
+    fn parse_suite_without_counts_has_no_declared_counts() {
+        let xml_content = r#"
+            <testsuite name="MyTestSuite">
+                <testcase name="test_success" classname="com.example.MyClass" time="0.05"/>
+            </testsuite>
+        "#;
+
+        let parser = JunitParser;
+        let suites = parser.parse_bytes(xml_content.as_bytes()).unwrap();
+
+        assert_eq!(suites[0].declared_counts, None);
+        assert_eq!(suites[0].hostname, None);
+        assert!(suites[0].errors.is_empty());
+    }
+
     #[parameterized(content = {
         r#"
             <testsuite>
@@ -102,7 +388,7 @@ mod tests {
         let file = create_temp_xml_file(content);
 
         let parser = JunitParser;
-        let result = parser.parse(&file.path());
+        let result = parser.parse(file.path());
 
         assert!(result.is_ok());
         let tests = result.unwrap();
@@ -127,7 +413,7 @@ mod tests {
         let file = create_temp_xml_file(content);
 
         let parser = JunitParser;
-        let result = parser.parse(&file.path());
+        let result = parser.parse(file.path());
 
         assert!(result.is_ok());
         let tests = result.unwrap();
@@ -147,7 +433,7 @@ mod tests {
 
         // Act
         let parser = JunitParser;
-        let result = parser.parse(&file.path());
+        let result = parser.parse(file.path());
 
         // Assert
         assert!(result.is_ok());
@@ -185,7 +471,7 @@ mod tests {
 
         // Act
         let parser = JunitParser;
-        let result = parser.parse(&file.path());
+        let result = parser.parse(file.path());
 
         // Assert
         assert!(result.is_ok());
@@ -213,7 +499,7 @@ mod tests {
 
         // Act
         let parser = JunitParser;
-        let result = parser.parse(&file.path());
+        let result = parser.parse(file.path());
 
         // Assert
         assert!(result.is_ok());
@@ -247,7 +533,7 @@ mod tests {
 
         // Act
         let parser = JunitParser;
-        let result = parser.parse(&file.path());
+        let result = parser.parse(file.path());
 
         // Assert
         assert!(result.is_ok());
@@ -282,7 +568,7 @@ mod tests {
 
         // Act
         let parser = JunitParser;
-        let result = parser.parse(&file.path());
+        let result = parser.parse(file.path());
 
         // Assert
         assert!(result.is_ok());
@@ -302,6 +588,35 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_case_failure_body_is_stack_trace() {
+        let xml_content = r#"
+            <testsuite name="MyTestSuite" tests="2" failures="1" errors="1">
+                <testcase name="failed" classname="com.example.MyClass" time="0.05">
+                    <failure message="expected: 1">
+                        org.opentest4j.AssertionFailedError: expected: 1
+                            at com.example.MyClass.failed(MyClass.java:10)
+                    </failure>
+                </testcase>
+                <testcase name="errored" classname="com.example.MyClass" time="0.05">
+                    <error message="boom"/>
+                </testcase>
+            </testsuite>
+        "#;
+        let file = create_temp_xml_file(xml_content);
+
+        let suites = JunitParser.parse(file.path()).unwrap();
+
+        assert_eq!(
+            suites[0].tests[0].stack_trace.as_deref(),
+            Some(
+                "org.opentest4j.AssertionFailedError: expected: 1\n    \
+                 at com.example.MyClass.failed(MyClass.java:10)"
+            )
+        );
+        assert_eq!(suites[0].tests[1].stack_trace, None);
+    }
+
     #[test]
     fn test_suite_with_error_tests() {
         // Arrange
@@ -320,7 +635,7 @@ mod tests {
 
         // Act
         let parser = JunitParser;
-        let result = parser.parse(&file.path());
+        let result = parser.parse(file.path());
 
         // Assert
         assert!(result.is_ok());
@@ -369,7 +684,7 @@ mod tests {
 
         // Act
         let parser = JunitParser;
-        let result = parser.parse(&file.path());
+        let result = parser.parse(file.path());
 
         // Assert
         assert!(result.is_ok());
@@ -1 +1,2 @@
-﻿pub mod junit;
+﻿pub mod external;
+pub mod junit;
@@ -0,0 +1,52 @@
+use crate::input_paths::STDIO_PATH;
+use crate::report_migrations::migrate_report;
+use crate::test_report::TestReport;
+use serde_json::Value;
+use std::fs;
+use std::io::{Read, Write};
+
+pub fn read_report(input_path: &str) -> Result<TestReport, String> {
+    let data = if input_path == STDIO_PATH {
+        let mut data = String::new();
+        std::io::stdin()
+            .read_to_string(&mut data)
+            .map_err(|e| format!("I/O error: {}", e))?;
+        data
+    } else {
+        fs::read_to_string(input_path).map_err(|e| format!("I/O error: {}", e))?
+    };
+
+    let value: Value = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+    let mut report: TestReport =
+        serde_json::from_value(migrate_report(value)?).map_err(|e| e.to_string())?;
+    report.normalize_timestamps();
+    for test in &mut report.tests {
+        test.ensure_id();
+    }
+
+    Ok(report)
+}
+
+pub fn write_report(report: &TestReport, output_path: &str) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(report).map_err(|e| e.to_string())?;
+
+    if output_path == STDIO_PATH {
+        let mut writer = std::io::stdout().lock();
+        return writeln!(writer, "{}", json).map_err(|e| format!("I/O error: {}", e));
+    }
+
+    fs::write(output_path, json).map_err(|e| format!("I/O error: {}", e))
+}
+
+/// Writes the output of a command to a file, or to stdout if no path or '-' is given.
+pub fn write_output(content: &str, output_path: Option<&str>) -> Result<(), String> {
+    match output_path {
+        Some(path) if path != STDIO_PATH => {
+            fs::write(path, content).map_err(|e| format!("I/O error: {}", e))
+        }
+        _ => {
+            let mut writer = std::io::stdout().lock();
+            write!(writer, "{}", content).map_err(|e| format!("I/O error: {}", e))
+        }
+    }
+}
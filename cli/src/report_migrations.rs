@@ -0,0 +1,144 @@
+use crate::test_report::REPORT_VERSION;
+use serde_json::{Map, Value, json};
+
+/// Upgrades a report read from disk to the current schema version.
+pub fn migrate_report(mut report: Value) -> Result<Value, String> {
+    let version = report_version(&report)?;
+    if version > u64::from(REPORT_VERSION) {
+        return Err(format!(
+            "Report version {} is newer than the supported version {}; please upgrade the cli",
+            version, REPORT_VERSION
+        ));
+    }
+
+    if version < 2 {
+        migrate_v1_to_v2(&mut report)?;
+    }
+
+    Ok(report)
+}
+
+fn report_version(report: &Value) -> Result<u64, String> {
+    match report.get("version") {
+        None => Ok(1),
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| format!("Invalid report version: {}", version)),
+    }
+}
+
+/// Version 1 reports describe a single run, identified only by the report timestamp.
+fn migrate_v1_to_v2(report: &mut Value) -> Result<(), String> {
+    let report_object = report
+        .as_object_mut()
+        .ok_or_else(|| "Invalid report: expected a JSON object".to_string())?;
+    let timestamp = report_object
+        .get("timestamp")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let run_id = format!("v1-{}", timestamp);
+
+    if let Some(tests) = report_object.get_mut("tests").and_then(Value::as_array_mut) {
+        for execution in tests
+            .iter_mut()
+            .filter_map(|test| test.get_mut("executions"))
+            .filter_map(Value::as_array_mut)
+            .flatten()
+            .filter_map(Value::as_object_mut)
+        {
+            set_run_id(execution, &run_id);
+        }
+    }
+
+    report_object.insert(
+        "runs".to_string(),
+        json!([{ "id": run_id, "timestamp": timestamp }]),
+    );
+    report_object.insert("version".to_string(), json!(2));
+    Ok(())
+}
+
+fn set_run_id(execution: &mut Map<String, Value>, run_id: &str) {
+    execution
+        .entry("runId")
+        .or_insert_with(|| Value::String(run_id.to_string()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v1_report_is_upgraded_to_v2_with_a_single_run() {
+        let report = json!({
+            "version": 1,
+            "timestamp": "2024-01-01T00:00:00Z",
+            "tests": [
+                {
+                    "name": "test1",
+                    "path": "suite",
+                    "executions": [
+                        { "timestamp": "2024-01-01T00:00:00Z", "status": "PASSED", "durationMs": 1 }
+                    ]
+                }
+            ]
+        });
+
+        let result = migrate_report(report).unwrap();
+
+        assert_eq!(result["version"], 2);
+        assert_eq!(
+            result["runs"],
+            json!([{ "id": "v1-2024-01-01T00:00:00Z", "timestamp": "2024-01-01T00:00:00Z" }])
+        );
+        assert_eq!(
+            result["tests"][0]["executions"][0]["runId"],
+            "v1-2024-01-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn report_without_version_is_treated_as_v1() {
+        let report = json!({ "timestamp": "2024-01-01T00:00:00Z", "tests": [] });
+
+        let result = migrate_report(report).unwrap();
+
+        assert_eq!(result["version"], 2);
+    }
+
+    #[test]
+    fn current_report_is_unchanged() {
+        let report = json!({
+            "version": 2,
+            "timestamp": "2024-01-01T00:00:00Z",
+            "runs": [{ "id": "run-1", "timestamp": "2024-01-01T00:00:00Z" }],
+            "tests": []
+        });
+
+        let result = migrate_report(report.clone()).unwrap();
+
+        assert_eq!(result, report);
+    }
+
+    #[test]
+    fn newer_report_returns_error() {
+        let report = json!({ "version": 3, "timestamp": "", "tests": [] });
+
+        let result = migrate_report(report);
+
+        assert_eq!(
+            result.unwrap_err(),
+            "Report version 3 is newer than the supported version 2; please upgrade the cli"
+        );
+    }
+
+    #[test]
+    fn invalid_version_returns_error() {
+        let report = json!({ "version": "two", "timestamp": "", "tests": [] });
+
+        let result = migrate_report(report);
+
+        assert!(result.unwrap_err().contains("Invalid report version"));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::test_report::TestReport;
+use jsonschema::error::ValidationErrorKind;
+use jsonschema::paths::LocationSegment;
+use serde_json::Value;
+
+pub const SCHEMA_ID: &str = "https://andrei2699.github.io/Test-Prism/schemas/report.schema.json";
+
+pub fn report_schema() -> Value {
+    let mut schema = serde_json::to_value(schemars::schema_for!(TestReport))
+        .expect("Failed to serialize the report schema");
+    if let Some(object) = schema.as_object_mut() {
+        object.insert("$id".to_string(), Value::String(SCHEMA_ID.to_string()));
+    }
+    schema
+}
+
+/// Validates a report against the schema and returns one readable message per error,
+/// e.g. `$.tests[0].executions[1].status: "UNKNOWN" is not one of ...`.
+pub fn validate_report(report: &Value) -> Result<Vec<String>, String> {
+    let validator = jsonschema::validator_for(&report_schema()).map_err(|e| e.to_string())?;
+
+    let mut errors: Vec<String> = validator
+        .iter_errors(report)
+        .map(|error| {
+            let mut path = format_path(error.instance_path().iter());
+            if let ValidationErrorKind::Required { property } = error.kind() {
+                if let Some(property) = property.as_str() {
+                    path = format!("{}.{}", path, property);
+                }
+                return format!("{}: is required", path);
+            }
+            format!("{}: {}", path, error)
+        })
+        .collect();
+    errors.sort();
+
+    Ok(errors)
+}
+
+fn format_path<'a>(segments: impl Iterator<Item = LocationSegment<'a>>) -> String {
+    let mut path = "$".to_string();
+    for segment in segments {
+        match segment {
+            LocationSegment::Property(property) => {
+                path.push('.');
+                path.push_str(&property);
+            }
+            LocationSegment::Index(index) => path.push_str(&format!("[{}]", index)),
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn valid_report() -> Value {
+        json!({
+            "version": 2,
+            "timestamp": "2025-01-06T15:34:21.123Z",
+            "runs": [{"id": "run-1", "timestamp": "2025-01-06T15:34:21.123Z"}],
+            "tests": [{
+                "id": "a1b2c3d4e5f60718",
+                "name": "test1",
+                "path": "suite",
+                "executions": [{
+                    "timestamp": "2025-01-06T15:34:21.123Z",
+                    "status": "FAILED",
+                    "durationMs": 12,
+                    "message": "expected 1",
+                    "runId": "run-1"
+                }],
+                "tags": ["smoke"]
+            }]
+        })
+    }
+
+    #[test]
+    fn schema_describes_report_fields() {
+        let schema = report_schema();
+
+        assert_eq!(schema["$id"], SCHEMA_ID);
+        assert_eq!(schema["title"], "Test Prism report");
+        let required = schema["required"].as_array().unwrap();
+        assert!(required.contains(&json!("version")));
+        assert!(required.contains(&json!("tests")));
+        assert!(!required.contains(&json!("runs")));
+    }
+
+    #[test]
+    fn valid_report_has_no_errors() {
+        assert_eq!(
+            validate_report(&valid_report()).unwrap(),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn version_1_report_is_valid() {
+        let report = json!({
+            "version": 1,
+            "timestamp": "2025-01-06 15:34:21.123 UTC",
+            "tests": [{
+                "name": "test1",
+                "path": "suite",
+                "executions": [{"timestamp": "2025-01-06 15:34:21.123 UTC", "status": "PASSED", "durationMs": 1}]
+            }]
+        });
+
+        assert_eq!(validate_report(&report).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn errors_contain_path_of_invalid_value() {
+        let mut report = valid_report();
+        report["tests"][0]["executions"][0]["status"] = json!("BROKEN");
+        report["tests"][0]["executions"][0]["durationMs"] = json!(-1);
+
+        let errors = validate_report(&report).unwrap();
+
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("$.tests[0].executions[0].durationMs: "));
+        assert!(errors[1].starts_with("$.tests[0].executions[0].status: "));
+    }
+
+    #[test]
+    fn errors_contain_path_of_missing_property() {
+        let mut report = valid_report();
+        report["tests"][0].as_object_mut().unwrap().remove("path");
+
+        let errors = validate_report(&report).unwrap();
+
+        assert_eq!(errors, vec!["$.tests[0].path: is required"]);
+    }
+
+    #[test]
+    fn newer_version_is_invalid() {
+        let mut report = valid_report();
+        report["version"] = json!(3);
+
+        let errors = validate_report(&report).unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("$.version: "));
+    }
+}
@@ -0,0 +1,174 @@
+use crate::test_report::{TestExecutionStatus, TestReportSuite, TestReportTest};
+use crate::timestamps::compare_timestamps;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Number of tests per status of their latest execution.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct StatusCounts {
+    pub passed: u64,
+    pub skipped: u64,
+    pub failed: u64,
+    pub error: u64,
+}
+
+impl StatusCounts {
+    pub fn from_tests<'a>(tests: impl IntoIterator<Item = &'a TestReportTest>) -> StatusCounts {
+        let mut counts = StatusCounts::default();
+        for execution in tests.into_iter().filter_map(|test| test.latest_execution()) {
+            match execution.status {
+                TestExecutionStatus::Passed => counts.passed += 1,
+                TestExecutionStatus::Skipped => counts.skipped += 1,
+                TestExecutionStatus::Failed => counts.failed += 1,
+                TestExecutionStatus::Error => counts.error += 1,
+            }
+        }
+        counts
+    }
+
+    pub fn total(&self) -> u64 {
+        self.passed + self.skipped + self.failed + self.error
+    }
+
+    pub fn failures(&self) -> u64 {
+        self.failed + self.error
+    }
+
+    /// Percentage of the tests that ran, i.e. were not skipped, that passed, where every errored
+    /// suite counts as a failed test. None if nothing ran.
+    pub fn pass_rate(&self, errored_suites: u64) -> Option<f64> {
+        let ran = self.passed + self.failures() + errored_suites;
+        (ran > 0).then(|| self.passed as f64 * 100.0 / ran as f64)
+    }
+}
+
+/// Latest suite of every name that has errors no test case accounts for, e.g. a crashed
+/// `@BeforeAll`.
+pub fn errored_suites(suites: &[TestReportSuite]) -> Vec<&TestReportSuite> {
+    let mut latest_suites: BTreeMap<&str, &TestReportSuite> = BTreeMap::new();
+    for suite in suites {
+        latest_suites
+            .entry(suite.name.as_str())
+            .and_modify(|latest| {
+                if compare_timestamps(&suite.timestamp, &latest.timestamp).is_gt() {
+                    *latest = suite;
+                }
+            })
+            .or_insert(suite);
+    }
+
+    latest_suites
+        .into_values()
+        .filter(|suite| !suite.errors.is_empty())
+        .collect()
+}
+
+/// Sum of the durations of the latest execution of every test.
+pub fn total_duration_ms<'a>(tests: impl IntoIterator<Item = &'a TestReportTest>) -> u64 {
+    tests
+        .into_iter()
+        .filter_map(|test| test.latest_execution())
+        .map(|execution| execution.duration_ms)
+        .sum()
+}
+
+/// Nearest-rank percentile of sorted values, 0 if there are none.
+pub fn percentile(sorted_values: &[u64], percent: f64) -> u64 {
+    if sorted_values.is_empty() {
+        return 0;
+    }
+
+    let rank = (percent / 100.0 * sorted_values.len() as f64).ceil() as usize;
+    sorted_values[rank.clamp(1, sorted_values.len()) - 1]
+}
+
+/// Median of the values, 0 if there are none.
+pub fn median(values: &[u64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+
+    let middle = sorted.len() / 2;
+    match sorted.len() {
+        0 => 0.0,
+        len if len % 2 == 0 => (sorted[middle - 1] + sorted[middle]) as f64 / 2.0,
+        _ => sorted[middle] as f64,
+    }
+}
+
+/// Median of the absolute deviations from the median, a measure of spread that outliers do
+/// not affect.
+pub fn median_absolute_deviation(values: &[u64], median: f64) -> f64 {
+    let mut deviations: Vec<f64> = values
+        .iter()
+        .map(|value| (*value as f64 - median).abs())
+        .collect();
+    deviations.sort_by(f64::total_cmp);
+
+    let middle = deviations.len() / 2;
+    match deviations.len() {
+        0 => 0.0,
+        len if len % 2 == 0 => (deviations[middle - 1] + deviations[middle]) / 2.0,
+        _ => deviations[middle],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::create_suite;
+    use parameterized::parameterized;
+
+    #[parameterized(percent = {0.0, 50.0, 90.0, 95.0, 100.0}, expected = {1, 5, 9, 10, 10})]
+    fn percentile_uses_the_nearest_rank(percent: f64, expected: u64) {
+        let values: Vec<u64> = (1..=10).collect();
+
+        assert_eq!(percentile(&values, percent), expected);
+    }
+
+    #[test]
+    fn median_and_deviation_ignore_outliers() {
+        let values = [100, 110, 90, 105, 5000];
+
+        let center = median(&values);
+
+        assert_eq!(center, 105.0);
+        assert_eq!(median_absolute_deviation(&values, center), 5.0);
+        assert_eq!(median(&[1, 4]), 2.5);
+    }
+
+    #[test]
+    fn percentile_of_no_values_is_zero() {
+        assert_eq!(percentile(&[], 50.0), 0);
+    }
+
+    #[test]
+    fn pass_rate_ignores_skipped_tests() {
+        let counts = StatusCounts {
+            passed: 3,
+            skipped: 5,
+            failed: 1,
+            error: 0,
+        };
+
+        assert_eq!(counts.pass_rate(0), Some(75.0));
+        assert_eq!(counts.pass_rate(2), Some(50.0));
+        assert_eq!(StatusCounts::default().pass_rate(0), None);
+    }
+
+    #[test]
+    fn errored_suites_are_those_of_the_latest_runs() {
+        let suites = vec![
+            create_suite("recovered", "2024-01-01T00:00:00Z", &["Setup failed"]),
+            create_suite("recovered", "2024-01-02T00:00:00Z", &[]),
+            create_suite("crashed", "2024-01-01T00:00:00Z", &[]),
+            create_suite("crashed", "2024-01-02T00:00:00Z", &["Setup failed"]),
+        ];
+
+        let names: Vec<&str> = errored_suites(&suites)
+            .iter()
+            .map(|suite| suite.name.as_str())
+            .collect();
+
+        assert_eq!(names, vec!["crashed"]);
+    }
+}
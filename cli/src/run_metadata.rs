@@ -0,0 +1,250 @@
+use crate::test_report::TestRun;
+use std::env;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunMetadata {
+    pub id: Option<String>,
+    pub ci_job_url: Option<String>,
+    pub commit_sha: Option<String>,
+    pub branch: Option<String>,
+    pub environment: Option<String>,
+    pub hostname: Option<String>,
+    pub format_version: Option<String>,
+}
+
+impl RunMetadata {
+    pub fn from_environment() -> RunMetadata {
+        Self::from_variables(|name| env::var(name).ok())
+    }
+
+    /// Detects the metadata exposed by GitHub Actions, GitLab CI, Jenkins and Azure Pipelines.
+    pub fn from_variables(variable: impl Fn(&str) -> Option<String>) -> RunMetadata {
+        let variable = |name: &str| variable(name).filter(|value| !value.is_empty());
+        let hostname = variable("HOSTNAME").or_else(|| variable("COMPUTERNAME"));
+
+        let ci_metadata = if variable("GITHUB_ACTIONS").is_some() {
+            let run_id = variable("GITHUB_RUN_ID");
+            RunMetadata {
+                id: run_id.as_ref().map(|run_id| {
+                    format!(
+                        "{}-{}",
+                        run_id,
+                        variable("GITHUB_RUN_ATTEMPT").unwrap_or("1".to_string())
+                    )
+                }),
+                ci_job_url: match (
+                    variable("GITHUB_SERVER_URL"),
+                    variable("GITHUB_REPOSITORY"),
+                    run_id,
+                ) {
+                    (Some(server), Some(repository), Some(run_id)) => {
+                        Some(format!("{}/{}/actions/runs/{}", server, repository, run_id))
+                    }
+                    _ => None,
+                },
+                commit_sha: variable("GITHUB_SHA"),
+                branch: variable("GITHUB_HEAD_REF").or_else(|| variable("GITHUB_REF_NAME")),
+                ..Default::default()
+            }
+        } else if variable("GITLAB_CI").is_some() {
+            RunMetadata {
+                id: variable("CI_JOB_ID"),
+                ci_job_url: variable("CI_JOB_URL"),
+                commit_sha: variable("CI_COMMIT_SHA"),
+                branch: variable("CI_COMMIT_REF_NAME"),
+                environment: variable("CI_ENVIRONMENT_NAME"),
+                ..Default::default()
+            }
+        } else if variable("JENKINS_URL").is_some() {
+            RunMetadata {
+                id: variable("BUILD_TAG"),
+                ci_job_url: variable("BUILD_URL"),
+                commit_sha: variable("GIT_COMMIT"),
+                branch: variable("BRANCH_NAME").or_else(|| variable("GIT_BRANCH")),
+                ..Default::default()
+            }
+        } else if variable("TF_BUILD").is_some() {
+            let build_id = variable("BUILD_BUILDID");
+            RunMetadata {
+                id: build_id.clone(),
+                ci_job_url: match (
+                    variable("SYSTEM_COLLECTIONURI"),
+                    variable("SYSTEM_TEAMPROJECT"),
+                    build_id,
+                ) {
+                    (Some(collection), Some(project), Some(build_id)) => Some(format!(
+                        "{}{}/_build/results?buildId={}",
+                        collection, project, build_id
+                    )),
+                    _ => None,
+                },
+                commit_sha: variable("BUILD_SOURCEVERSION"),
+                branch: variable("BUILD_SOURCEBRANCHNAME"),
+                ..Default::default()
+            }
+        } else {
+            RunMetadata::default()
+        };
+
+        RunMetadata {
+            hostname,
+            ..ci_metadata
+        }
+    }
+
+    /// Fills the values that are not set with the ones from `fallback`.
+    pub fn or(self, fallback: RunMetadata) -> RunMetadata {
+        RunMetadata {
+            id: self.id.or(fallback.id),
+            ci_job_url: self.ci_job_url.or(fallback.ci_job_url),
+            commit_sha: self.commit_sha.or(fallback.commit_sha),
+            branch: self.branch.or(fallback.branch),
+            environment: self.environment.or(fallback.environment),
+            hostname: self.hostname.or(fallback.hostname),
+            format_version: self.format_version.or(fallback.format_version),
+        }
+    }
+
+    pub fn to_test_run(&self, timestamp: &str, report_type: &str) -> TestRun {
+        TestRun {
+            id: self
+                .id
+                .clone()
+                .unwrap_or_else(|| format!("run-{}", timestamp)),
+            timestamp: timestamp.to_string(),
+            ci_job_url: self.ci_job_url.clone(),
+            commit_sha: self.commit_sha.clone(),
+            branch: self.branch.clone(),
+            environment: self.environment.clone(),
+            hostname: self.hostname.clone(),
+            tool_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            report_type: Some(report_type.to_string()),
+            format_version: self.format_version.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn metadata_from(variables: &[(&str, &str)]) -> RunMetadata {
+        let variables: HashMap<String, String> = variables
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        RunMetadata::from_variables(|name| variables.get(name).cloned())
+    }
+
+    #[test]
+    fn detects_github_actions() {
+        let result = metadata_from(&[
+            ("GITHUB_ACTIONS", "true"),
+            ("GITHUB_RUN_ID", "42"),
+            ("GITHUB_RUN_ATTEMPT", "2"),
+            ("GITHUB_SERVER_URL", "https://github.com"),
+            ("GITHUB_REPOSITORY", "owner/repo"),
+            ("GITHUB_SHA", "abc123"),
+            ("GITHUB_REF_NAME", "main"),
+            ("HOSTNAME", "runner-1"),
+        ]);
+
+        assert_eq!(
+            result,
+            RunMetadata {
+                id: Some("42-2".to_string()),
+                ci_job_url: Some("https://github.com/owner/repo/actions/runs/42".to_string()),
+                commit_sha: Some("abc123".to_string()),
+                branch: Some("main".to_string()),
+                hostname: Some("runner-1".to_string()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn detects_gitlab_ci() {
+        let result = metadata_from(&[
+            ("GITLAB_CI", "true"),
+            ("CI_JOB_ID", "7"),
+            ("CI_JOB_URL", "https://gitlab.com/owner/repo/-/jobs/7"),
+            ("CI_COMMIT_SHA", "def456"),
+            ("CI_COMMIT_REF_NAME", "feature"),
+            ("CI_ENVIRONMENT_NAME", "staging"),
+        ]);
+
+        assert_eq!(
+            result,
+            RunMetadata {
+                id: Some("7".to_string()),
+                ci_job_url: Some("https://gitlab.com/owner/repo/-/jobs/7".to_string()),
+                commit_sha: Some("def456".to_string()),
+                branch: Some("feature".to_string()),
+                environment: Some("staging".to_string()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn detects_jenkins() {
+        let result = metadata_from(&[
+            ("JENKINS_URL", "https://jenkins"),
+            ("BUILD_TAG", "jenkins-job-3"),
+            ("BUILD_URL", "https://jenkins/job/3/"),
+            ("GIT_COMMIT", "fff000"),
+            ("GIT_BRANCH", "origin/main"),
+        ]);
+
+        assert_eq!(result.id, Some("jenkins-job-3".to_string()));
+        assert_eq!(
+            result.ci_job_url,
+            Some("https://jenkins/job/3/".to_string())
+        );
+        assert_eq!(result.branch, Some("origin/main".to_string()));
+    }
+
+    #[test]
+    fn without_ci_only_hostname_is_detected() {
+        let result = metadata_from(&[("COMPUTERNAME", "DESKTOP"), ("GITHUB_SHA", "")]);
+
+        assert_eq!(
+            result,
+            RunMetadata {
+                hostname: Some("DESKTOP".to_string()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn explicit_values_take_precedence_over_fallback() {
+        let explicit = RunMetadata {
+            branch: Some("release".to_string()),
+            ..Default::default()
+        };
+        let detected = RunMetadata {
+            id: Some("42".to_string()),
+            branch: Some("main".to_string()),
+            ..Default::default()
+        };
+
+        let result = explicit.or(detected);
+
+        assert_eq!(result.id, Some("42".to_string()));
+        assert_eq!(result.branch, Some("release".to_string()));
+    }
+
+    #[test]
+    fn test_run_id_defaults_to_timestamp() {
+        let result = RunMetadata::default().to_test_run("2024-01-01T00:00:00Z", "junit");
+
+        assert_eq!(result.id, "run-2024-01-01T00:00:00Z");
+        assert_eq!(result.report_type, Some("junit".to_string()));
+        assert_eq!(
+            result.tool_version,
+            Some(env!("CARGO_PKG_VERSION").to_string())
+        );
+    }
+}
@@ -0,0 +1,486 @@
+use crate::test_report::{TestExecutionStatus, TestReportTest};
+use regex::Regex;
+use std::fmt;
+
+/// Selects the tests a tag rule applies to.
+#[derive(Debug, Clone)]
+pub enum TagMatcher {
+    And(Vec<TagMatcher>),
+    Or(Vec<TagMatcher>),
+    Not(Box<TagMatcher>),
+    Name(TextPattern),
+    Path(TextPattern),
+    Id(TextPattern),
+    /// Failure message of the latest execution.
+    Message(TextPattern),
+    /// Status of the latest execution.
+    Status(TestExecutionStatus),
+    /// Duration of the latest execution, in milliseconds.
+    Duration(Comparison, f64),
+    /// Matches when any tag of the test matches.
+    Tag(TextPattern),
+}
+
+#[derive(Debug, Clone)]
+pub enum TextPattern {
+    Equals(String),
+    Regex(Regex),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Equal,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+impl TagMatcher {
+    pub fn matches(&self, test: &TestReportTest) -> bool {
+        let latest_execution = test.latest_execution();
+        match self {
+            TagMatcher::And(matchers) => matchers.iter().all(|matcher| matcher.matches(test)),
+            TagMatcher::Or(matchers) => matchers.iter().any(|matcher| matcher.matches(test)),
+            TagMatcher::Not(matcher) => !matcher.matches(test),
+            TagMatcher::Name(pattern) => pattern.matches(&test.name),
+            TagMatcher::Path(pattern) => pattern.matches(&test.path),
+            TagMatcher::Id(pattern) => pattern.matches(&test.id),
+            TagMatcher::Message(pattern) => latest_execution
+                .and_then(|execution| execution.message.as_deref())
+                .is_some_and(|message| pattern.matches(message)),
+            TagMatcher::Status(status) => {
+                latest_execution.is_some_and(|execution| execution.status == *status)
+            }
+            TagMatcher::Duration(comparison, duration_ms) => {
+                latest_execution.is_some_and(|execution| {
+                    comparison.holds(execution.duration_ms as f64, *duration_ms)
+                })
+            }
+            TagMatcher::Tag(pattern) => test.tags.iter().flatten().any(|tag| pattern.matches(tag)),
+        }
+    }
+}
+
+impl TextPattern {
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            TextPattern::Equals(expected) => text == expected,
+            TextPattern::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+impl Comparison {
+    fn holds(self, actual: f64, expected: f64) -> bool {
+        match self {
+            Comparison::Equal => actual == expected,
+            Comparison::Greater => actual > expected,
+            Comparison::GreaterOrEqual => actual >= expected,
+            Comparison::Less => actual < expected,
+            Comparison::LessOrEqual => actual <= expected,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Equals,
+    NotEquals,
+    Matches,
+    NotMatches,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    OpenParen,
+    CloseParen,
+    Operator(Operator),
+    Word(String),
+    Quoted(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::OpenParen => write!(f, "'('"),
+            Token::CloseParen => write!(f, "')'"),
+            Token::Operator(operator) => write!(f, "operator {:?}", operator),
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Quoted(text) => write!(f, "\"{}\"", text),
+        }
+    }
+}
+
+/// Parses a tag matcher expression, e.g.
+/// `status == failed and message ~ Timeout and not tag == quarantined`.
+///
+/// Conditions have the form `<field> <operator> <value>` and are combined with `and`, `or`,
+/// `not` and parentheses, where `not` binds tighter than `and`, which binds tighter than `or`.
+pub fn parse_tag_matcher(expression: &str) -> Result<TagMatcher, String> {
+    let tokens = tokenize(expression)?;
+    let mut parser = MatcherParser {
+        tokens,
+        position: 0,
+    };
+    let matcher = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("Unexpected {} in tag matcher", token));
+    }
+    Ok(matcher)
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = expression.trim_start();
+
+    while let Some(character) = rest.chars().next() {
+        let length = match character {
+            '(' => {
+                tokens.push(Token::OpenParen);
+                1
+            }
+            ')' => {
+                tokens.push(Token::CloseParen);
+                1
+            }
+            '=' | '!' | '~' | '<' | '>' => {
+                let (operator, length) = match rest.get(..2) {
+                    Some("==") => (Operator::Equals, 2),
+                    Some("!=") => (Operator::NotEquals, 2),
+                    Some("!~") => (Operator::NotMatches, 2),
+                    Some(">=") => (Operator::GreaterOrEqual, 2),
+                    Some("<=") => (Operator::LessOrEqual, 2),
+                    _ if character == '~' => (Operator::Matches, 1),
+                    _ if character == '>' => (Operator::Greater, 1),
+                    _ if character == '<' => (Operator::Less, 1),
+                    _ => return Err(format!("Unknown operator in tag matcher: {}", rest)),
+                };
+                tokens.push(Token::Operator(operator));
+                length
+            }
+            '"' | '\'' => {
+                let end = rest[1..]
+                    .find(character)
+                    .ok_or_else(|| "Unterminated string in tag matcher".to_string())?;
+                tokens.push(Token::Quoted(rest[1..end + 1].to_string()));
+                end + 2
+            }
+            _ => {
+                let length = rest
+                    .find(|c: char| c.is_whitespace() || "()=!~<>\"'".contains(c))
+                    .unwrap_or(rest.len());
+                tokens.push(Token::Word(rest[..length].to_string()));
+                length
+            }
+        };
+        rest = rest[length..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+struct MatcherParser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl MatcherParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| "Unexpected end of tag matcher".to_string())?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn next_is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Result<TagMatcher, String> {
+        self.parse_logical("or", TagMatcher::Or, Self::parse_and)
+    }
+
+    fn parse_and(&mut self) -> Result<TagMatcher, String> {
+        self.parse_logical("and", TagMatcher::And, Self::parse_not)
+    }
+
+    fn parse_logical(
+        &mut self,
+        keyword: &str,
+        combine: fn(Vec<TagMatcher>) -> TagMatcher,
+        parse_operand: fn(&mut Self) -> Result<TagMatcher, String>,
+    ) -> Result<TagMatcher, String> {
+        let mut matchers = vec![parse_operand(self)?];
+        while self.next_is_keyword(keyword) {
+            self.position += 1;
+            matchers.push(parse_operand(self)?);
+        }
+
+        if matchers.len() == 1 {
+            return Ok(matchers.remove(0));
+        }
+        Ok(combine(matchers))
+    }
+
+    fn parse_not(&mut self) -> Result<TagMatcher, String> {
+        if self.next_is_keyword("not") {
+            self.position += 1;
+            return Ok(TagMatcher::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<TagMatcher, String> {
+        if self.peek() == Some(&Token::OpenParen) {
+            self.position += 1;
+            let matcher = self.parse_or()?;
+            match self.next()? {
+                Token::CloseParen => return Ok(matcher),
+                token => return Err(format!("Expected ')' but found {} in tag matcher", token)),
+            }
+        }
+
+        let field = match self.next()? {
+            Token::Word(field) => field.to_ascii_lowercase(),
+            token => return Err(format!("Expected a field but found {}", token)),
+        };
+        let operator = match self.next()? {
+            Token::Operator(operator) => operator,
+            token => return Err(format!("Expected an operator but found {}", token)),
+        };
+        let value = match self.next()? {
+            Token::Word(value) | Token::Quoted(value) => value,
+            token => return Err(format!("Expected a value but found {}", token)),
+        };
+
+        let matcher = match field.as_str() {
+            "name" => TagMatcher::Name(text_pattern(operator, value)?),
+            "path" => TagMatcher::Path(text_pattern(operator, value)?),
+            "id" => TagMatcher::Id(text_pattern(operator, value)?),
+            "message" => TagMatcher::Message(text_pattern(operator, value)?),
+            "tag" => TagMatcher::Tag(text_pattern(operator, value)?),
+            "status" => match operator {
+                Operator::Equals | Operator::NotEquals => TagMatcher::Status(status(&value)?),
+                _ => return Err(format!("Operator {:?} is not valid for status", operator)),
+            },
+            "duration" => {
+                let comparison = match operator {
+                    Operator::Equals | Operator::NotEquals => Comparison::Equal,
+                    Operator::Greater => Comparison::Greater,
+                    Operator::GreaterOrEqual => Comparison::GreaterOrEqual,
+                    Operator::Less => Comparison::Less,
+                    Operator::LessOrEqual => Comparison::LessOrEqual,
+                    _ => return Err(format!("Operator {:?} is not valid for duration", operator)),
+                };
+                TagMatcher::Duration(comparison, duration_ms(&value)?)
+            }
+            _ => return Err(format!("Unknown field in tag matcher: {}", field)),
+        };
+
+        Ok(match operator {
+            Operator::NotEquals | Operator::NotMatches => TagMatcher::Not(Box::new(matcher)),
+            _ => matcher,
+        })
+    }
+}
+
+fn text_pattern(operator: Operator, value: String) -> Result<TextPattern, String> {
+    match operator {
+        Operator::Equals | Operator::NotEquals => Ok(TextPattern::Equals(value)),
+        Operator::Matches | Operator::NotMatches => Regex::new(&value)
+            .map(TextPattern::Regex)
+            .map_err(|e| format!("Invalid regular expression in tag matcher: {}", e)),
+        _ => Err(format!("Operator {:?} is not valid for text", operator)),
+    }
+}
+
+fn status(value: &str) -> Result<TestExecutionStatus, String> {
+    match value.to_ascii_uppercase().as_str() {
+        "PASSED" => Ok(TestExecutionStatus::Passed),
+        "SKIPPED" => Ok(TestExecutionStatus::Skipped),
+        "FAILED" => Ok(TestExecutionStatus::Failed),
+        "ERROR" => Ok(TestExecutionStatus::Error),
+        _ => Err(format!("Unknown status in tag matcher: {}", value)),
+    }
+}
+
+/// Parses a duration in milliseconds, with an optional `ms`, `s` or `m` unit.
+fn duration_ms(value: &str) -> Result<f64, String> {
+    let (number, scale) = if let Some(number) = value.strip_suffix("ms") {
+        (number, 1.0)
+    } else if let Some(number) = value.strip_suffix('s') {
+        (number, 1_000.0)
+    } else if let Some(number) = value.strip_suffix('m') {
+        (number, 60_000.0)
+    } else {
+        (value, 1.0)
+    };
+
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite() && *number >= 0.0)
+        .map(|number| number * scale)
+        .ok_or_else(|| format!("Invalid duration in tag matcher: {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::create_execution;
+    use crate::test_report::TestExecution;
+    use parameterized::parameterized;
+
+    fn create_test(
+        name: &str,
+        status: TestExecutionStatus,
+        duration_ms: u64,
+        message: Option<&str>,
+        tags: Option<Vec<&str>>,
+    ) -> TestReportTest {
+        TestReportTest {
+            id: format!("id-{}", name),
+            name: name.to_string(),
+            path: format!("path/to/{}", name),
+            executions: vec![TestExecution {
+                duration_ms,
+                message: message.map(|message| message.to_string()),
+                ..create_execution(status)
+            }],
+            tags: tags.map(|tags| tags.into_iter().map(|s| s.to_string()).collect()),
+            ..Default::default()
+        }
+    }
+
+    fn tests() -> Vec<TestReportTest> {
+        vec![
+            create_test(
+                "login",
+                TestExecutionStatus::Passed,
+                100,
+                None,
+                Some(vec!["smoke"]),
+            ),
+            create_test(
+                "checkout",
+                TestExecutionStatus::Failed,
+                7_000,
+                Some("Timeout after 5000 ms"),
+                Some(vec!["owner:payments", "quarantined"]),
+            ),
+            create_test(
+                "refund",
+                TestExecutionStatus::Error,
+                2_500,
+                Some("Connection refused"),
+                Some(vec!["owner:payments"]),
+            ),
+            create_test("search", TestExecutionStatus::Skipped, 0, None, None),
+        ]
+    }
+
+    fn matching_names(matcher: &TagMatcher) -> Vec<String> {
+        tests()
+            .into_iter()
+            .filter(|test| matcher.matches(test))
+            .map(|test| test.name)
+            .collect()
+    }
+
+    #[parameterized(expression = {
+        "name == login",
+        "name ~ '^(login|search)$'",
+        "name !~ 'o'",
+        "path ~ path/to/re",
+        "id == id-search",
+        "status == failed",
+        "status != PASSED",
+        "duration > 5s",
+        "duration >= 2500ms",
+        "duration < 0.1s",
+        "duration == 0",
+        "message ~ Timeout",
+        "message ~ \"(?i)connection REFUSED\"",
+        "message !~ Timeout",
+        "tag == smoke",
+        "tag ~ '^owner:'",
+        "tag != quarantined",
+        "tag ~ owner and not tag == quarantined",
+        "status == failed or status == error and duration < 3s",
+        "(status == failed or status == error) and duration < 3s",
+        "NOT (tag == smoke OR status == skipped)",
+        "not not name == login",
+    }, expected = {
+        vec!["login"],
+        vec!["login", "search"],
+        vec!["refund", "search"],
+        vec!["refund"],
+        vec!["search"],
+        vec!["checkout"],
+        vec!["checkout", "refund", "search"],
+        vec!["checkout"],
+        vec!["checkout", "refund"],
+        vec!["search"],
+        vec!["search"],
+        vec!["checkout"],
+        vec!["refund"],
+        vec!["login", "refund", "search"],
+        vec!["login"],
+        vec!["checkout", "refund"],
+        vec!["login", "refund", "search"],
+        vec!["refund"],
+        vec!["checkout", "refund"],
+        vec!["refund"],
+        vec!["checkout", "refund"],
+        vec!["login"],
+    })]
+    fn matchers_select_tests(expression: &str, expected: Vec<&str>) {
+        let matcher = parse_tag_matcher(expression).unwrap();
+
+        assert_eq!(matching_names(&matcher), expected);
+    }
+
+    #[test]
+    fn tests_without_executions_have_no_status_duration_or_message() {
+        let test = TestReportTest {
+            name: "empty".to_string(),
+            ..Default::default()
+        };
+
+        for expression in ["status == passed", "duration >= 0", "message ~ .*"] {
+            assert!(!parse_tag_matcher(expression).unwrap().matches(&test));
+        }
+    }
+
+    #[parameterized(expression = {
+        "",
+        "name",
+        "path/to/.*",
+        "name = x",
+        "name == x and",
+        "(name == x",
+        "name ~ '('",
+        "name > x",
+        "status == broken",
+        "status ~ failed",
+        "duration > fast",
+        "duration ~ 5",
+        "owner == x",
+        "name == 'x",
+    })]
+    fn invalid_matchers_are_errors(expression: &str) {
+        assert!(parse_tag_matcher(expression).is_err());
+    }
+}
@@ -0,0 +1,277 @@
+use crate::tag_matcher::{TagMatcher, parse_tag_matcher};
+use crate::test_report::TestReportTest;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagOperation {
+    #[default]
+    Add,
+    Remove,
+    Update,
+}
+
+/// Adds, removes or replaces the tags of the tests selected by a matcher.
+#[derive(Debug, Clone)]
+pub struct TagRule {
+    pub matcher: TagMatcher,
+    pub operation: TagOperation,
+    pub tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    rules: Vec<RuleDefinition>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleDefinition {
+    #[serde(rename = "match")]
+    matcher: String,
+    #[serde(default)]
+    operation: TagOperation,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Reads the rules of a TOML rules file. The rules of included files, relative to the file
+/// that includes them, come before the rules of the file itself.
+pub fn read_tag_rules(path: &str) -> Result<Vec<TagRule>, String> {
+    let mut rules = Vec::new();
+    read_rules_file(Path::new(path), &mut Vec::new(), &mut rules)?;
+    Ok(rules)
+}
+
+fn read_rules_file(
+    path: &Path,
+    including_files: &mut Vec<PathBuf>,
+    rules: &mut Vec<TagRule>,
+) -> Result<(), String> {
+    let canonical_path = fs::canonicalize(path)
+        .map_err(|e| format!("I/O error reading {}: {}", path.display(), e))?;
+    if including_files.contains(&canonical_path) {
+        return Err(format!("Rules file {} includes itself", path.display()));
+    }
+
+    let data = fs::read_to_string(path)
+        .map_err(|e| format!("I/O error reading {}: {}", path.display(), e))?;
+    let file: RulesFile = toml::from_str(&data)
+        .map_err(|e| format!("Invalid rules file {}: {}", path.display(), e))?;
+
+    including_files.push(canonical_path);
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    for include in &file.include {
+        read_rules_file(&base_dir.join(include), including_files, rules)?;
+    }
+    including_files.pop();
+
+    for (index, definition) in file.rules.into_iter().enumerate() {
+        let rule = TagRule::new(&definition.matcher, definition.operation, definition.tags)
+            .map_err(|e| format!("Invalid rule {} of {}: {}", index + 1, path.display(), e))?;
+        rules.push(rule);
+    }
+    Ok(())
+}
+
+impl TagRule {
+    pub fn new(
+        expression: &str,
+        operation: TagOperation,
+        tags: Vec<String>,
+    ) -> Result<TagRule, String> {
+        let tags: Vec<String> = tags.into_iter().filter(|tag| !tag.is_empty()).collect();
+        if tags.is_empty() && operation != TagOperation::Update {
+            return Err("No tags to add or remove".to_string());
+        }
+
+        Ok(TagRule {
+            matcher: parse_tag_matcher(expression)?,
+            operation,
+            tags,
+        })
+    }
+
+    pub fn apply(&self, test: &mut TestReportTest) {
+        if !self.matcher.matches(test) {
+            return;
+        }
+
+        test.tags = match self.operation {
+            TagOperation::Add => add_tags_to_test(test.tags.as_ref(), &self.tags),
+            TagOperation::Remove => remove_tags_from_test(test.tags.as_ref(), &self.tags),
+            TagOperation::Update => Some(self.tags.clone()),
+        };
+    }
+}
+
+/// Applies the rules in order, so a rule sees the tags of the rules before it.
+pub fn apply_tag_rules(tests: &mut [TestReportTest], rules: &[TagRule]) {
+    for test in tests {
+        for rule in rules {
+            rule.apply(test);
+        }
+    }
+}
+
+pub(crate) fn add_tags_to_test(
+    current_tags: Option<&Vec<String>>,
+    tags_to_add: &[String],
+) -> Option<Vec<String>> {
+    let mut new_tags = current_tags.cloned().unwrap_or_default();
+    for tag in tags_to_add {
+        if !new_tags.contains(tag) {
+            new_tags.push(tag.clone());
+        }
+    }
+    Some(new_tags)
+}
+
+fn remove_tags_from_test(
+    current_tags: Option<&Vec<String>>,
+    tags_to_remove: &[String],
+) -> Option<Vec<String>> {
+    current_tags.map(|tags| {
+        tags.iter()
+            .filter(|tag| !tags_to_remove.contains(tag))
+            .cloned()
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::create_execution;
+    use crate::test_report::TestExecutionStatus;
+    use tempfile::{TempDir, tempdir};
+
+    fn create_test(
+        name: &str,
+        status: TestExecutionStatus,
+        tags: Option<Vec<&str>>,
+    ) -> TestReportTest {
+        TestReportTest {
+            id: name.to_string(),
+            name: name.to_string(),
+            path: format!("path/to/{}", name),
+            executions: vec![create_execution(status)],
+            tags: tags.map(|tags| tags.into_iter().map(|s| s.to_string()).collect()),
+            ..Default::default()
+        }
+    }
+
+    fn write_files(files: &[(&str, &str)]) -> TempDir {
+        let dir = tempdir().unwrap();
+        for (name, content) in files {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    fn rules_path(dir: &TempDir) -> String {
+        dir.path().join("rules.toml").to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn rules_are_applied_in_order_after_included_rules() {
+        let dir = write_files(&[
+            (
+                "rules.toml",
+                r#"
+                # Ownership is shared with the other pipelines
+                include = ["shared/owners.toml"]
+
+                [[rules]]
+                match = "status == failed and tag == 'owner:payments'"
+                tags = ["triage"]
+
+                [[rules]]
+                match = "path ~ 'path/to/(login|legacy)'"
+                operation = "remove"
+                tags = ["owner:payments"]
+                "#,
+            ),
+            (
+                "shared/owners.toml",
+                r#"
+                [[rules]]
+                match = "name ~ '^(checkout|login)$'"
+                tags = ["owner:payments"]
+                "#,
+            ),
+        ]);
+        let mut tests = vec![
+            create_test("checkout", TestExecutionStatus::Failed, None),
+            create_test("login", TestExecutionStatus::Failed, Some(vec!["smoke"])),
+        ];
+
+        let rules = read_tag_rules(&rules_path(&dir)).unwrap();
+        apply_tag_rules(&mut tests, &rules);
+
+        assert_eq!(
+            tests[0].tags,
+            Some(vec!["owner:payments".to_string(), "triage".to_string()])
+        );
+        assert_eq!(
+            tests[1].tags,
+            Some(vec!["smoke".to_string(), "triage".to_string()])
+        );
+    }
+
+    #[test]
+    fn update_rule_replaces_the_tags() {
+        let rule = TagRule::new("status == passed", TagOperation::Update, Vec::new()).unwrap();
+        let mut test = create_test("login", TestExecutionStatus::Passed, Some(vec!["flaky"]));
+
+        rule.apply(&mut test);
+
+        assert_eq!(test.tags, Some(Vec::new()));
+    }
+
+    #[test]
+    fn include_cycles_are_errors() {
+        let dir = write_files(&[
+            ("rules.toml", r#"include = ["other.toml"]"#),
+            ("other.toml", r#"include = ["rules.toml"]"#),
+        ]);
+
+        let error = read_tag_rules(&rules_path(&dir)).unwrap_err();
+
+        assert!(error.contains("includes itself"), "{}", error);
+    }
+
+    #[test]
+    fn invalid_rules_are_errors() {
+        for (content, expected) in [
+            (
+                "[[rules]]\nmatch = \"name ==\"\ntags = [\"a\"]",
+                "Invalid rule 1",
+            ),
+            ("[[rules]]\nmatch = \"name == a\"", "Invalid rule 1"),
+            (
+                "[[rules]]\nmatch = \"name == a\"\noperation = \"toggle\"",
+                "Invalid rules file",
+            ),
+            (
+                "[[rules]]\nexpression = \"name == a\"",
+                "Invalid rules file",
+            ),
+            ("include = [\"missing.toml\"]", "I/O error"),
+        ] {
+            let dir = write_files(&[("rules.toml", content)]);
+
+            let error = read_tag_rules(&rules_path(&dir)).unwrap_err();
+
+            assert!(error.starts_with(expected), "{}", error);
+        }
+    }
+}
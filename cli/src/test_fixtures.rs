@@ -0,0 +1,45 @@
+//! Reports and executions shared by the unit tests.
+
+use crate::test_report::{
+    REPORT_VERSION, TestExecution, TestExecutionStatus, TestReport, TestReportSuite, TestReportTest,
+};
+
+/// An execution of 100 ms on 2024-01-01 without a message or run.
+pub fn create_execution(status: TestExecutionStatus) -> TestExecution {
+    TestExecution {
+        timestamp: "2024-01-01T00:00:00Z".to_string(),
+        status,
+        duration_ms: 100,
+        ..Default::default()
+    }
+}
+
+/// A report of the tests, without runs or suites.
+pub fn create_report(tests: Vec<TestReportTest>) -> TestReport {
+    TestReport {
+        version: REPORT_VERSION,
+        timestamp: "2024-01-01T00:00:00Z".to_string(),
+        runs: Vec::new(),
+        suites: Vec::new(),
+        tests,
+    }
+}
+
+/// A suite without test cases, which is an error if it has errors of its own.
+pub fn create_suite(name: &str, timestamp: &str, errors: &[&str]) -> TestReportSuite {
+    TestReportSuite {
+        name: name.to_string(),
+        timestamp: timestamp.to_string(),
+        status: if errors.is_empty() {
+            TestExecutionStatus::Passed
+        } else {
+            TestExecutionStatus::Error
+        },
+        duration_ms: 0,
+        hostname: None,
+        declared_counts: None,
+        actual_counts: Default::default(),
+        errors: errors.iter().map(|error| error.to_string()).collect(),
+        run_id: None,
+    }
+}
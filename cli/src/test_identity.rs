@@ -0,0 +1,100 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Patterns of the values that change between runs and their placeholders, shared by test
+/// identities and failure signatures.
+pub(crate) const VOLATILE_PATTERNS: [(&str, &str); 3] = [
+    (
+        r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:?\d{2})?",
+        "<timestamp>",
+    ),
+    (
+        r"(?i)\b[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}\b",
+        "<uuid>",
+    ),
+    (r"(?i)\b0x[0-9a-f]+\b|@[0-9a-f]{6,}\b", "<address>"),
+];
+
+static VOLATILE_FRAGMENTS: LazyLock<Vec<(Regex, &str)>> = LazyLock::new(|| {
+    VOLATILE_PATTERNS
+        .into_iter()
+        .chain([(r"\b\d{10,}\b", "<number>")])
+        .map(|(pattern, replacement)| (Regex::new(pattern).unwrap(), replacement))
+        .collect()
+});
+
+/// Replaces fragments that change between runs, such as timestamps, UUIDs, memory
+/// addresses and epoch values, so that the identity of a test stays stable.
+pub fn normalize_identity(identity: &str) -> String {
+    VOLATILE_FRAGMENTS.iter().fold(
+        identity.trim().to_string(),
+        |normalized, (regex, replacement)| {
+            regex.replace_all(&normalized, *replacement).into_owned()
+        },
+    )
+}
+
+/// Builds the deterministic id of a test from its path and format-specific identity.
+pub fn test_id(path: &str, identity: &str) -> String {
+    let key = format!("{}\u{1f}{}", path, normalize_identity(identity));
+    format!("{:016x}", fnv1a_64(key.as_bytes()))
+}
+
+pub(crate) fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parameterized::parameterized;
+
+    #[parameterized(identity = {
+        "creates order at 2024-01-01T10:00:00.123Z",
+        "creates order at 2024-01-01 10:00:00",
+        "creates order for 123e4567-e89b-12d3-a456-426614174000",
+        "creates order in Object@1b6d3586",
+        "creates order in 0x7ffee4b8",
+        "creates order with seed 1704103200000",
+    }, expected = {
+        "creates order at <timestamp>",
+        "creates order at <timestamp>",
+        "creates order for <uuid>",
+        "creates order in Object<address>",
+        "creates order in <address>",
+        "creates order with seed <number>",
+    })]
+    fn volatile_fragments_are_normalized(identity: &str, expected: &str) {
+        assert_eq!(normalize_identity(identity), expected);
+    }
+
+    #[test]
+    fn short_numbers_are_kept() {
+        assert_eq!(normalize_identity("test[1] a=2, b=3"), "test[1] a=2, b=3");
+    }
+
+    #[test]
+    fn test_id_is_stable_for_volatile_fragments() {
+        let first = test_id("suite", "run at 2024-01-01T10:00:00Z");
+        let second = test_id("suite", "run at 2025-06-30T23:59:59Z");
+
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 16);
+    }
+
+    #[test]
+    fn test_id_depends_on_path_and_identity() {
+        assert_eq!(test_id("suite", "test"), test_id("suite", "test"));
+        assert_ne!(test_id("suite", "test"), test_id("other", "test"));
+        assert_ne!(test_id("suite", "test"), test_id("suite", "other"));
+        assert_ne!(test_id("a", "b c"), test_id("a b", "c"));
+    }
+
+    #[test]
+    fn test_id_uses_fnv1a_hash() {
+        assert_eq!(fnv1a_64(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a_64(b"a"), 0xaf63dc4c8601ec8c);
+    }
+}
@@ -1,8 +1,16 @@
-﻿#[derive(Debug, Clone)]
+﻿use crate::test_report::TestCounts;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default)]
 pub struct TestSuite {
     pub name: String,
     pub duration: f64,
     pub timestamp: String,
+    pub hostname: Option<String>,
+    /// Counts declared by the report itself, if the format has them.
+    pub declared_counts: Option<TestCounts>,
+    /// Errors of the suite that do not belong to a test, e.g. failed setup or teardown.
+    pub errors: Vec<String>,
     pub tests: Vec<Test>,
 }
 
@@ -17,6 +25,22 @@ pub enum TestStatus {
 #[derive(Debug, Clone)]
 pub struct Test {
     pub name: String,
+    pub identity: Option<String>,
     pub time: f64,
     pub status: TestStatus,
+    pub attachments: Vec<TestAttachment>,
+    pub parameters: BTreeMap<String, String>,
+    /// Name of the parameterized test the invocation belongs to.
+    pub parent: Option<String>,
+    pub start_time: Option<String>,
+    /// Stack trace or other details of a failure or error.
+    pub stack_trace: Option<String>,
+}
+
+/// A file produced by a test, e.g. a screenshot, as referenced by the report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestAttachment {
+    pub name: Option<String>,
+    pub path: String,
+    pub mime_type: Option<String>,
 }
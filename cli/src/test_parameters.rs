@@ -0,0 +1,214 @@
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+/// `[index] arguments`, optionally prefixed by the method, e.g. `[1] a=2, b=3` (JUnit 5)
+/// or `test[1] a=2, b=3`.
+static INDEXED_INVOCATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([\w.$]+(?:\([^()]*\))?)?\[(\d+)\]:?\s+(.+)$").unwrap());
+
+/// `method[...]` at the end of the name, e.g. `test_add[1-2-3]` (pytest), `testAdd[0: 1, 2]`
+/// (JUnit 4) or `testParameterized(String)[1]` (Maven Surefire).
+static BRACKETED_SUFFIX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([\w.$]+(?:\([^()]*\))?)\[([^\[\]]+)\]$").unwrap());
+
+static INDEXED_ARGUMENTS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\d+(?::\s*(.*))?$").unwrap());
+
+/// `method(arguments)`, e.g. `Add(1,2)` or `Greets("Ann",True)` (NUnit).
+static ARGUMENT_LIST: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([\w.$]+)\((.*\S.*)\)$").unwrap());
+
+/// Numbers, strings, characters, booleans and null, which tell arguments from a signature
+/// such as `testParameterized(String)`.
+static LITERAL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^(?:[-+]?\.?\d[\w.]*|"(?:[^"\\]|\\.)*"|'(?:[^'\\]|\\.)+'|(?i:true|false|null))$"#)
+        .unwrap()
+});
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ParameterizedName {
+    /// Name of the test all invocations belong to, if the name contains it.
+    pub parent: Option<String>,
+    pub parameters: BTreeMap<String, String>,
+}
+
+/// Extracts the parameters of a parameterized test invocation from its name. Named
+/// arguments (`a=2, b=3`) keep their names, other arguments are keyed by their position and
+/// pytest ids, which do not expose the argument boundaries, are kept whole under `id`.
+pub fn parse_parameterized_name(name: &str) -> Option<ParameterizedName> {
+    if let Some(captures) = INDEXED_INVOCATION.captures(name) {
+        let parent = captures
+            .get(1)
+            .and_then(|parent| non_empty(parent.as_str()));
+        let parameters = parse_arguments(&captures[3]);
+        // A method followed by prose, e.g. `array[0] is empty`, is not an invocation.
+        let named = parameters.keys().all(|key| key.parse::<usize>().is_err());
+        return (parent.is_none() || named).then_some(ParameterizedName { parent, parameters });
+    }
+
+    if let Some(captures) = BRACKETED_SUFFIX.captures(name) {
+        let content = &captures[2];
+        let parameters = match INDEXED_ARGUMENTS.captures(content) {
+            Some(indexed) => indexed
+                .get(1)
+                .map(|arguments| parse_arguments(arguments.as_str()))
+                .unwrap_or_default(),
+            None if content.contains(['=', ',']) => parse_arguments(content),
+            None => BTreeMap::from([("id".to_string(), content.to_string())]),
+        };
+        return Some(ParameterizedName {
+            parent: non_empty(&captures[1]),
+            parameters,
+        });
+    }
+
+    let captures = ARGUMENT_LIST.captures(name)?;
+    let arguments = split_argument_list(&captures[2]);
+    if !arguments.iter().any(|argument| LITERAL.is_match(argument)) {
+        return None;
+    }
+    Some(ParameterizedName {
+        parent: non_empty(&captures[1]),
+        parameters: arguments
+            .into_iter()
+            .enumerate()
+            .map(|(index, argument)| (index.to_string(), unquote(argument).to_string()))
+            .collect(),
+    })
+}
+
+/// Splits `1,"a, b",'c'` at the commas outside of quotes, brackets and parentheses.
+fn split_argument_list(arguments: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut escaped = false;
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (index, character) in arguments.char_indices() {
+        match (quote, character) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(open), _) if character == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(character),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth = depth.saturating_sub(1),
+            (None, ',') if depth == 0 => {
+                parts.push(arguments[start..index].trim());
+                start = index + 1;
+            }
+            (None, _) => {}
+        }
+    }
+    parts.push(arguments[start..].trim());
+    parts
+}
+
+fn unquote(argument: &str) -> &str {
+    argument
+        .strip_prefix('"')
+        .and_then(|argument| argument.strip_suffix('"'))
+        .unwrap_or(argument)
+}
+
+fn parse_arguments(arguments: &str) -> BTreeMap<String, String> {
+    let arguments: Vec<&str> = arguments
+        .split(", ")
+        .map(str::trim)
+        .filter(|argument| !argument.is_empty())
+        .collect();
+    let named: Option<BTreeMap<String, String>> = arguments
+        .iter()
+        .map(|argument| {
+            let (name, value) = argument.split_once('=')?;
+            let name = name.trim();
+            (!name.is_empty() && !name.contains(char::is_whitespace))
+                .then(|| (name.to_string(), value.trim().to_string()))
+        })
+        .collect();
+
+    named.unwrap_or_else(|| {
+        arguments
+            .iter()
+            .enumerate()
+            .map(|(index, argument)| (index.to_string(), argument.to_string()))
+            .collect()
+    })
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parameterized::parameterized;
+
+    fn parameters(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[parameterized(name = {
+        "[1] one",
+        "[2] a=2, b=3",
+        "test[1] a=2, b=3",
+        "testAdd[0: 1, 2]",
+        "testParameterized(String)[1]",
+        "test_add[1-2-3]",
+        "test_login[user=admin]",
+        "Add(1,2)",
+        "Greets(\"Ann, Lee\",True,null)",
+    }, expected_parent = {
+        None,
+        None,
+        Some("test"),
+        Some("testAdd"),
+        Some("testParameterized(String)"),
+        Some("test_add"),
+        Some("test_login"),
+        Some("Add"),
+        Some("Greets"),
+    }, expected_parameters = {
+        &[("0", "one")],
+        &[("a", "2"), ("b", "3")],
+        &[("a", "2"), ("b", "3")],
+        &[("0", "1"), ("1", "2")],
+        &[],
+        &[("id", "1-2-3")],
+        &[("user", "admin")],
+        &[("0", "1"), ("1", "2")],
+        &[("0", "Ann, Lee"), ("1", "True"), ("2", "null")],
+    })]
+    fn parse_parameterized_names(
+        name: &str,
+        expected_parent: Option<&str>,
+        expected_parameters: &[(&str, &str)],
+    ) {
+        let result = parse_parameterized_name(name).unwrap();
+
+        assert_eq!(result.parent.as_deref(), expected_parent);
+        assert_eq!(result.parameters, parameters(expected_parameters));
+    }
+
+    #[parameterized(name = {
+        "testSuccess()",
+        "should [not] be parsed as parameters",
+        "[1]",
+        "array[0] is empty",
+        "returns [] for empty input",
+        "should be parsed [never]",
+        "parses nested[[1]]",
+        "testParameterized(String)",
+        "compare(List<String>, int)",
+        "returns a value (for now)",
+    })]
+    fn names_without_parameters_are_not_parameterized(name: &str) {
+        assert_eq!(parse_parameterized_name(name), None);
+    }
+}
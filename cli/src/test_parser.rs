@@ -1,6 +1,17 @@
 ﻿use crate::test_models::TestSuite;
+use std::fs::File;
+use std::io::Read;
 use std::path::Path;
 
-pub trait TestParser {
-    fn parse(&self, file_path: &Path) -> Result<Vec<TestSuite>, String>;
+pub trait TestParser: Send + Sync {
+    fn parse_reader(&self, reader: &mut dyn Read) -> Result<Vec<TestSuite>, String>;
+
+    fn parse_bytes(&self, content: &[u8]) -> Result<Vec<TestSuite>, String> {
+        self.parse_reader(&mut &content[..])
+    }
+
+    fn parse(&self, file_path: &Path) -> Result<Vec<TestSuite>, String> {
+        let mut file = File::open(file_path).map_err(|e| format!("I/O error: {}", e))?;
+        self.parse_reader(&mut file)
+    }
 }
@@ -1,15 +1,87 @@
-﻿use crate::test_models::TestStatus;
+﻿use crate::test_identity::test_id;
+use crate::test_models::{TestStatus, TestSuite};
+use crate::timestamps::{SourceTimezone, compare_timestamps, normalize_timestamp};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 
-#[derive(Serialize, Deserialize, Clone)]
+pub const REPORT_VERSION: u8 = 2;
+
+/// Normalized test report consumed by the Test Prism dashboard.
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[schemars(title = "Test Prism report")]
 pub struct TestReport {
+    /// Version of the report format.
+    #[schemars(range(min = 1, max = REPORT_VERSION))]
     pub version: u8,
+    /// Time the report was created.
     pub timestamp: String,
+    /// Runs whose executions are part of the report.
+    #[serde(default)]
+    pub runs: Vec<TestRun>,
+    /// Suites of every run, including suites that failed before running any test.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suites: Vec<TestReportSuite>,
     pub tests: Vec<TestReportTest>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// A single invocation of the tests, e.g. one CI job.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+pub struct TestRun {
+    pub id: String,
+    pub timestamp: String,
+    #[serde(rename = "ciJobUrl", skip_serializing_if = "Option::is_none")]
+    pub ci_job_url: Option<String>,
+    #[serde(rename = "commitSha", skip_serializing_if = "Option::is_none")]
+    pub commit_sha: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(rename = "toolVersion", skip_serializing_if = "Option::is_none")]
+    pub tool_version: Option<String>,
+    #[serde(rename = "reportType", skip_serializing_if = "Option::is_none")]
+    pub report_type: Option<String>,
+    #[serde(rename = "formatVersion", skip_serializing_if = "Option::is_none")]
+    pub format_version: Option<String>,
+}
+
+/// A suite or other container of tests as reported by a single run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TestReportSuite {
+    pub name: String,
+    pub timestamp: String,
+    pub status: TestExecutionStatus,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// Counts declared by the suite in the original report.
+    #[serde(rename = "declaredCounts", skip_serializing_if = "Option::is_none")]
+    pub declared_counts: Option<TestCounts>,
+    /// Counts of the test cases found in the suite.
+    #[serde(rename = "actualCounts")]
+    pub actual_counts: TestCounts,
+    /// Setup and teardown errors, and counts declared by the suite that no test case accounts for.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+    #[serde(rename = "runId", skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+pub struct TestCounts {
+    pub tests: u64,
+    pub failures: u64,
+    pub errors: u64,
+    pub skipped: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum TestExecutionStatus {
+    #[default]
     #[serde(rename = "PASSED")]
     Passed,
     #[serde(rename = "SKIPPED")]
@@ -20,26 +92,97 @@ pub enum TestExecutionStatus {
     Error,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct TestExecution {
     pub timestamp: String,
     pub status: TestExecutionStatus,
     #[serde(rename = "durationMs")]
     pub duration_ms: u64,
+    /// Duration in microseconds, for tests that take less than a millisecond.
+    #[serde(rename = "durationUs", skip_serializing_if = "Option::is_none")]
+    pub duration_us: Option<u64>,
+    /// Whether the report contained a negative or non-numeric duration, which was replaced by 0.
+    #[serde(
+        rename = "invalidDuration",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub invalid_duration: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    /// Id of the run in `runs` that produced the execution.
+    #[serde(rename = "runId", skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+    /// Start of the test, if the report has it or it could be estimated.
+    #[serde(rename = "startTime", skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    /// Whether `startTime` was estimated from the start of the suite and the durations of the
+    /// tests before it.
+    #[serde(
+        rename = "startTimeEstimated",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub start_time_estimated: bool,
+    /// Stack trace or other details of a failure or error.
+    #[serde(rename = "stackTrace", skip_serializing_if = "Option::is_none")]
+    pub stack_trace: Option<String>,
+    /// Id of the cluster of failures with the same normalized message and stack frame.
+    #[serde(rename = "clusterId", skip_serializing_if = "Option::is_none")]
+    pub cluster_id: Option<String>,
+}
+
+/// A file produced by a test execution, such as a screenshot, video, trace or HAR file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Attachment {
+    pub name: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    /// Path of the file, relative to the report when it was copied into an attachments directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Base64 encoded content of small files that were inlined into the report.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct TestReportTest {
+    /// Identifier of the test that stays the same between runs.
+    #[serde(default)]
+    pub id: String,
     pub name: String,
     pub path: String,
     pub executions: Vec<TestExecution>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<Vec<String>>,
+    /// Arguments of a parameterized test invocation, keyed by name or by position.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub parameters: BTreeMap<String, String>,
+    /// Name of the parameterized test the invocation belongs to, used to group invocations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
 }
 
 impl TestExecutionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TestExecutionStatus::Passed => "PASSED",
+            TestExecutionStatus::Skipped => "SKIPPED",
+            TestExecutionStatus::Failed => "FAILED",
+            TestExecutionStatus::Error => "ERROR",
+        }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            TestExecutionStatus::Failed | TestExecutionStatus::Error
+        )
+    }
+
     pub fn from_test_status(status: &TestStatus) -> TestExecutionStatus {
         match status {
             TestStatus::Passed => TestExecutionStatus::Passed,
@@ -50,6 +193,147 @@ impl TestExecutionStatus {
     }
 }
 
+/// Converts a duration in seconds to whole milliseconds, rounded to the nearest one.
+/// Negative, infinite and NaN durations are invalid.
+pub fn duration_ms_from_seconds(seconds: f64) -> Option<u64> {
+    is_valid_duration(seconds).then(|| (seconds * 1_000.0).round() as u64)
+}
+
+/// Converts a duration in seconds to whole microseconds, rounded to the nearest one.
+/// Negative, infinite and NaN durations are invalid.
+pub fn duration_us_from_seconds(seconds: f64) -> Option<u64> {
+    is_valid_duration(seconds).then(|| (seconds * 1_000_000.0).round() as u64)
+}
+
+fn is_valid_duration(seconds: f64) -> bool {
+    seconds.is_finite() && seconds >= 0.0
+}
+
+impl TestReport {
+    /// Creates a report with a subset of the tests, keeping only the runs that produced their
+    /// executions and the suites of these runs that contain some of the tests.
+    pub fn with_tests(&self, tests: Vec<TestReportTest>) -> TestReport {
+        let run_ids: HashSet<&str> = tests
+            .iter()
+            .flat_map(|test| &test.executions)
+            .filter_map(|execution| execution.run_id.as_deref())
+            .collect();
+        let suite_keys: HashSet<(&str, Option<&str>)> = tests
+            .iter()
+            .flat_map(|test| {
+                test.executions
+                    .iter()
+                    .map(|execution| (test.path.as_str(), execution.run_id.as_deref()))
+                    .chain([(test.path.as_str(), None)])
+            })
+            .collect();
+
+        TestReport {
+            version: self.version,
+            timestamp: self.timestamp.clone(),
+            runs: self
+                .runs
+                .iter()
+                .filter(|run| run_ids.contains(run.id.as_str()))
+                .cloned()
+                .collect(),
+            suites: self
+                .suites
+                .iter()
+                .filter(|suite| {
+                    suite_keys.contains(&(suite.name.as_str(), suite.run_id.as_deref()))
+                })
+                .cloned()
+                .collect(),
+            tests,
+        }
+    }
+
+    /// Rewrites every timestamp that can be parsed as RFC 3339 UTC, so reports written by
+    /// older versions sort correctly.
+    pub fn normalize_timestamps(&mut self) {
+        let timestamps = std::iter::once(&mut self.timestamp)
+            .chain(self.runs.iter_mut().map(|run| &mut run.timestamp))
+            .chain(self.suites.iter_mut().map(|suite| &mut suite.timestamp))
+            .chain(
+                self.tests
+                    .iter_mut()
+                    .flat_map(|test| test.executions.iter_mut())
+                    .map(|execution| &mut execution.timestamp),
+            );
+
+        for timestamp in timestamps {
+            if let Some(normalized) = normalize_timestamp(timestamp, SourceTimezone::Utc) {
+                *timestamp = normalized;
+            }
+        }
+    }
+}
+
+impl TestCounts {
+    pub fn from_tests(suite: &TestSuite) -> TestCounts {
+        let mut counts = TestCounts {
+            tests: suite.tests.len() as u64,
+            ..Default::default()
+        };
+        for test in &suite.tests {
+            match test.status {
+                TestStatus::Passed => {}
+                TestStatus::Skipped(_) => counts.skipped += 1,
+                TestStatus::Failed(_) => counts.failures += 1,
+                TestStatus::Error(_) => counts.errors += 1,
+            }
+        }
+        counts
+    }
+}
+
+impl TestReportSuite {
+    /// A suite is an error if it has its own errors, e.g. a crashed `@BeforeAll`, or if it
+    /// declares more tests, failures, errors or skipped tests than its test cases report.
+    pub fn from_test_suite(suite: &TestSuite, timestamp: String) -> TestReportSuite {
+        let actual_counts = TestCounts::from_tests(suite);
+        let mut errors = suite.errors.clone();
+        if let Some(declared) = suite.declared_counts {
+            for (label, declared, actual) in [
+                ("tests", declared.tests, actual_counts.tests),
+                ("failures", declared.failures, actual_counts.failures),
+                ("errors", declared.errors, actual_counts.errors),
+                ("skipped tests", declared.skipped, actual_counts.skipped),
+            ] {
+                if declared > actual {
+                    errors.push(format!(
+                        "Suite declares {} {} but its test cases report {}",
+                        declared, label, actual
+                    ));
+                }
+            }
+        }
+
+        let status = if !errors.is_empty() || actual_counts.errors > 0 {
+            TestExecutionStatus::Error
+        } else if actual_counts.failures > 0 {
+            TestExecutionStatus::Failed
+        } else if actual_counts.tests > 0 && actual_counts.skipped == actual_counts.tests {
+            TestExecutionStatus::Skipped
+        } else {
+            TestExecutionStatus::Passed
+        };
+
+        TestReportSuite {
+            name: suite.name.clone(),
+            timestamp,
+            status,
+            duration_ms: duration_ms_from_seconds(suite.duration).unwrap_or(0),
+            hostname: suite.hostname.clone(),
+            declared_counts: suite.declared_counts,
+            actual_counts,
+            errors,
+            run_id: None,
+        }
+    }
+}
+
 impl TestExecution {
     pub fn message_from_test_status(status: &TestStatus) -> Option<String> {
         match status {
@@ -60,3 +344,30 @@ impl TestExecution {
         }
     }
 }
+
+impl TestReportTest {
+    /// Derives the id of tests from reports written before ids were introduced.
+    pub fn ensure_id(&mut self) {
+        if self.id.is_empty() {
+            self.id = test_id(&self.path, &self.name);
+        }
+    }
+
+    /// Whether the id is the one derived by `ensure_id`, which the ids of parsed tests with a
+    /// format-specific identity never match.
+    pub fn has_legacy_id(&self) -> bool {
+        self.id == test_id(&self.path, &self.name)
+    }
+
+    /// Returns the most recent execution, the first one if several share a timestamp, as the
+    /// dashboard does.
+    pub fn latest_execution(&self) -> Option<&TestExecution> {
+        self.executions
+            .iter()
+            .enumerate()
+            .max_by(|(a_index, a), (b_index, b)| {
+                compare_timestamps(&a.timestamp, &b.timestamp).then(b_index.cmp(a_index))
+            })
+            .map(|(_, execution)| execution)
+    }
+}
@@ -0,0 +1,222 @@
+use chrono::{
+    DateTime, FixedOffset, Local, LocalResult, NaiveDateTime, SecondsFormat, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+const NAIVE_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+const OFFSET_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M:%S%.f%z", "%Y-%m-%d %H:%M:%S%.f %z"];
+
+/// Time zone of source timestamps that do not specify one.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SourceTimezone {
+    #[default]
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+    Named(Tz),
+}
+
+impl FromStr for SourceTimezone {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "UTC" | "utc" | "Z" => Ok(SourceTimezone::Utc),
+            "local" => Ok(SourceTimezone::Local),
+            value => FixedOffset::from_str(value)
+                .map(SourceTimezone::Fixed)
+                .or_else(|_| Tz::from_str(value).map(SourceTimezone::Named))
+                .map_err(|_| {
+                    format!(
+                        "Unknown timezone: {}. Use UTC, local, an offset such as +02:00 or a name such as Europe/Berlin",
+                        value
+                    )
+                }),
+        }
+    }
+}
+
+impl SourceTimezone {
+    fn to_utc(self, timestamp: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            SourceTimezone::Utc => Some(timestamp.and_utc()),
+            SourceTimezone::Local => earliest(Local.from_local_datetime(&timestamp)),
+            SourceTimezone::Fixed(offset) => earliest(offset.from_local_datetime(&timestamp)),
+            SourceTimezone::Named(zone) => earliest(zone.from_local_datetime(&timestamp)),
+        }
+    }
+}
+
+fn earliest<Zone: TimeZone>(result: LocalResult<DateTime<Zone>>) -> Option<DateTime<Utc>> {
+    result
+        .earliest()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+/// Parses the timestamp formats found in reports; values without a zone are read as UTC.
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    parse_timestamp_in(value, SourceTimezone::Utc)
+}
+
+/// Parses the timestamp formats found in reports; values without a zone are read in `timezone`.
+pub fn parse_timestamp_in(value: &str, timezone: SourceTimezone) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    if let Some(timestamp) = OFFSET_FORMATS
+        .iter()
+        .find_map(|format| DateTime::parse_from_str(value, format).ok())
+    {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+
+    let (naive_value, timezone) = match value.strip_suffix(" UTC") {
+        Some(naive_value) => (naive_value, SourceTimezone::Utc),
+        None => (value, timezone),
+    };
+    NAIVE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(naive_value, format).ok())
+        .and_then(|timestamp| timezone.to_utc(timestamp))
+}
+
+/// Formats a timestamp as RFC 3339 in UTC, e.g. `2025-01-06T15:34:21.123Z`. Fractional
+/// seconds are only written when present.
+pub fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Orders timestamps by the time they denote, since the width of fractional seconds varies.
+/// Unparsable timestamps come first and are compared as text.
+pub fn compare_timestamps(a: &str, b: &str) -> Ordering {
+    match (parse_timestamp(a), parse_timestamp(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => a.cmp(b),
+    }
+}
+
+/// Converts a timestamp in any supported format to RFC 3339 UTC.
+pub fn normalize_timestamp(value: &str, timezone: SourceTimezone) -> Option<String> {
+    parse_timestamp_in(value, timezone).map(format_timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parameterized::parameterized;
+
+    #[parameterized(value = {
+        "2024-01-01T10:00:00Z",
+        "2024-01-01T12:00:00+02:00",
+        "2024-01-01T12:00:00+0200",
+        "2024-01-01T10:00:00",
+        "2024-01-01 10:00:00 UTC",
+        " 2024-01-01T10:00:00.000Z ",
+    })]
+    fn parses_supported_formats(value: &str) {
+        let expected = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+
+        assert_eq!(parse_timestamp(value), Some(expected));
+    }
+
+    #[test]
+    fn keeps_fractional_seconds() {
+        let result = parse_timestamp("2025-01-06 15:34:21.123 UTC").unwrap();
+
+        assert_eq!(result.timestamp_subsec_millis(), 123);
+    }
+
+    #[parameterized(value = { "", "yesterday", "2024-13-01T00:00:00Z" })]
+    fn returns_none_for_invalid_values(value: &str) {
+        assert_eq!(parse_timestamp(value), None);
+    }
+
+    #[parameterized(value = {
+        "2025-01-06 15:34:21.123 UTC",
+        "2025-01-06T17:34:21.123+02:00",
+        "2025-01-06T15:34:21.123",
+    }, expected = {
+        "2025-01-06T15:34:21.123Z",
+        "2025-01-06T15:34:21.123Z",
+        "2025-01-06T15:34:21.123Z",
+    })]
+    fn normalizes_to_rfc3339_utc(value: &str, expected: &str) {
+        assert_eq!(
+            normalize_timestamp(value, SourceTimezone::Utc).as_deref(),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn omits_fractional_seconds_when_zero() {
+        assert_eq!(
+            normalize_timestamp("2024-01-01T10:00:00", SourceTimezone::Utc).as_deref(),
+            Some("2024-01-01T10:00:00Z")
+        );
+    }
+
+    #[parameterized(timezone = { "+02:00", "Europe/Bucharest" }, expected = {
+        "2024-07-01T08:00:00Z",
+        "2024-07-01T07:00:00Z",
+    })]
+    fn reads_zone_less_values_in_source_timezone(timezone: &str, expected: &str) {
+        let timezone = SourceTimezone::from_str(timezone).unwrap();
+
+        assert_eq!(
+            normalize_timestamp("2024-07-01T10:00:00", timezone).as_deref(),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn source_timezone_does_not_apply_to_values_with_a_zone() {
+        let timezone = SourceTimezone::from_str("Europe/Bucharest").unwrap();
+
+        assert_eq!(
+            normalize_timestamp("2024-07-01T10:00:00Z", timezone).as_deref(),
+            Some("2024-07-01T10:00:00Z")
+        );
+    }
+
+    #[parameterized(a = {
+        "2024-01-01T00:00:00Z",
+        "2024-01-01T00:00:00.500Z",
+        "2024-01-01T02:00:00+02:00",
+        "invalid",
+    }, b = {
+        "2024-01-01T00:00:00.500Z",
+        "2024-01-01T00:00:00.5Z",
+        "2024-01-01T00:00:00Z",
+        "2024-01-01T00:00:00Z",
+    }, expected = {
+        Ordering::Less,
+        Ordering::Equal,
+        Ordering::Equal,
+        Ordering::Less,
+    })]
+    fn compares_timestamps_by_time(a: &str, b: &str, expected: Ordering) {
+        assert_eq!(compare_timestamps(a, b), expected);
+    }
+
+    #[parameterized(value = { "UTC", "local", "-05:30", "America/New_York" })]
+    fn parses_timezones(value: &str) {
+        assert!(SourceTimezone::from_str(value).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_timezone() {
+        let result = SourceTimezone::from_str("Mars/Olympus");
+
+        assert!(
+            result
+                .unwrap_err()
+                .starts_with("Unknown timezone: Mars/Olympus.")
+        );
+    }
+}
@@ -1,12 +1,15 @@
 ﻿use assert_json_diff::assert_json_eq;
+use cli::commands::parse_command::{ParseOptions, parse_command};
 use parameterized::parameterized;
-use cli::commands::parse_command::parse_command;
 use serde_json::Value;
 use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
 use tempfile::tempdir;
 
 #[parameterized(input_file = {
     "tests/data/junit/input/folder",
+    "tests/data/junit/input/TEST-com.testprism.BeforeAllFailureTest.xml",
     "tests/data/junit/input/TEST-com.testprism.DisplayNameTest.xml",
     "tests/data/junit/input/TEST-com.testprism.FailedTest.xml",
     "tests/data/junit/input/TEST-com.testprism.NestedTests$DeeperNestedTest$EvenDeeperNestedTest.xml",
@@ -18,6 +21,7 @@ use tempfile::tempdir;
     "tests/data/junit/input/TEST-com.testprism.TestWithMultipleTypes.xml",
 }, expected_output_file = {
     "tests/data/junit/output/folder.json",
+    "tests/data/junit/output/TEST-com.testprism.BeforeAllFailureTest.json",
     "tests/data/junit/output/TEST-com.testprism.DisplayNameTest.json",
     "tests/data/junit/output/TEST-com.testprism.FailedTest.json",
     "tests/data/junit/output/TEST-com.testprism.NestedTests$DeeperNestedTest$EvenDeeperNestedTest.json",
@@ -33,13 +37,13 @@ fn junit_parse_command(input_file: &str, expected_output_file: &str) {
     let output_path = temp_dir.path().join("output.json");
     let output_path_str = output_path.to_str().unwrap().to_string();
 
-    parse_command(
-        "junit".to_string(),
-        input_file.to_string(),
-        output_path_str.to_string(),
-        "2025-01-06T15:34:21.123Z".to_string(),
-        vec![],
-    );
+    parse_command(ParseOptions {
+        report_type: "junit".to_string(),
+        inputs: vec![input_file.to_string()],
+        output: output_path_str.to_string(),
+        current_date: "2025-01-06T15:34:21.123Z".to_string(),
+        ..Default::default()
+    });
 
     let actual_content = fs::read_to_string(output_path).expect("Failed to read output file");
     let expected_content =
@@ -49,5 +53,105 @@ fn junit_parse_command(input_file: &str, expected_output_file: &str) {
         serde_json::from_str(&actual_content).expect("Failed to parse actual JSON");
     let expected_json: Value =
         serde_json::from_str(&expected_content).expect("Failed to parse expected JSON");
+    assert_json_eq!(
+        without_tool_version(actual_json),
+        without_tool_version(expected_json)
+    );
+}
+
+/// Removes the version of the cli from the runs, so the expected outputs don't change with each release.
+fn without_tool_version(mut report: Value) -> Value {
+    if let Some(runs) = report["runs"].as_array_mut() {
+        for run in runs {
+            if let Some(run) = run.as_object_mut() {
+                run.remove("toolVersion");
+            }
+        }
+    }
+    report
+}
+
+#[test]
+fn junit_parse_command_from_stdin_to_stdout() {
+    let input_content = fs::read("tests/data/junit/input/TEST-com.testprism.SuccessTest.xml")
+        .expect("Failed to read input file");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cli"))
+        .args([
+            "parse",
+            "--report-type",
+            "junit",
+            "--input",
+            "-",
+            "--output",
+            "-",
+            "--run-id",
+            "run-2025-01-06T15:34:21.123Z",
+            "--timestamp",
+            "2025-01-06 17:34:21.123+02:00",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to start cli");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(&input_content)
+        .expect("Failed to write to stdin");
+    let output = child.wait_with_output().expect("Failed to wait for cli");
+
+    assert!(output.status.success());
+    let actual_json: Value =
+        serde_json::from_slice(&output.stdout).expect("Failed to parse actual JSON");
+    let expected_content =
+        fs::read_to_string("tests/data/junit/output/TEST-com.testprism.SuccessTest.json")
+            .expect("Failed to read expected output file");
+    let mut expected_json: Value =
+        serde_json::from_str(&expected_content).expect("Failed to parse expected JSON");
+    expected_json["runs"] = actual_json["runs"].clone();
     assert_json_eq!(actual_json, expected_json);
 }
+
+#[test]
+fn published_schema_matches_report_types() {
+    let published = fs::read_to_string("../docs/public/schemas/report.schema.json").unwrap();
+
+    let published: Value = serde_json::from_str(&published).unwrap();
+
+    assert_json_eq!(published, cli::report_schema::report_schema());
+}
+
+#[test]
+fn generated_reports_match_schema() {
+    for entry in fs::read_dir("tests/data/junit/output").unwrap() {
+        let path = entry.unwrap().path();
+        let report: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+        let errors = cli::report_schema::validate_report(&report).unwrap();
+
+        assert!(errors.is_empty(), "{}: {:?}", path.display(), errors);
+    }
+}
+
+#[test]
+fn validate_command_exits_with_error_for_invalid_report() {
+    let dir = tempdir().unwrap();
+    let report_path = dir.path().join("report.json");
+    fs::write(
+        &report_path,
+        r#"{"version": 2, "timestamp": "now", "tests": [{"name": "a"}]}"#,
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cli"))
+        .args(["validate", "--input", report_path.to_str().unwrap()])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("$.tests[0].executions: is required"));
+    assert!(stdout.contains("$.tests[0].path: is required"));
+}
@@ -1,20 +1,72 @@
 ﻿export interface TestReport {
   version: number;
   timestamp: string;
+  runs?: TestRun[];
+  suites?: TestSuite[];
   tests: Test[];
 }
 
+export interface TestSuite {
+  name: string;
+  timestamp: string;
+  status: ExecutionStatus;
+  durationMs: number;
+  hostname?: string;
+  declaredCounts?: TestCounts;
+  actualCounts: TestCounts;
+  errors?: string[];
+  runId?: string;
+}
+
+export interface TestCounts {
+  tests: number;
+  failures: number;
+  errors: number;
+  skipped: number;
+}
+
+export interface TestRun {
+  id: string;
+  timestamp: string;
+  ciJobUrl?: string;
+  commitSha?: string;
+  branch?: string;
+  environment?: string;
+  hostname?: string;
+  toolVersion?: string;
+  reportType?: string;
+  formatVersion?: string;
+}
+
 export interface Test {
+  id?: string;
   name: string;
   path: string;
   executions: TestExecution[];
   tags?: string[];
+  parameters?: Record<string, string>;
+  parent?: string;
 }
 
 export interface TestExecution {
   timestamp: string;
   status: TestExecutionStatus;
   durationMs: number;
+  durationUs?: number;
+  invalidDuration?: boolean;
+  runId?: string;
+  attachments?: Attachment[];
+  startTime?: string;
+  startTimeEstimated?: boolean;
+  stackTrace?: string;
+  clusterId?: string;
+}
+
+export interface Attachment {
+  name: string;
+  mimeType: string;
+  path?: string;
+  content?: string;
 }
 
 export type TestExecutionStatus = 'PASSED' | 'FAILED' | 'SKIPPED' | 'ERROR';